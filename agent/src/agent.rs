@@ -421,6 +421,8 @@ impl Agent {
     }
 
     async fn detect_tablet_mode(&self, enable: bool) -> Result<()> {
+        self.claim_orientation_poll(enable).await;
+
         let enabled = {
             let task = self.state.tablet_mode_task.read().await;
             task.is_some()
@@ -516,6 +518,8 @@ impl Agent {
     }
 
     async fn detect_orientation(&self, enable: bool) -> Result<()> {
+        self.claim_orientation_poll(enable).await;
+
         let enabled = {
             let task = self.state.orientation_task.read().await;
             task.is_some()
@@ -552,22 +556,53 @@ impl Agent {
         Ok(())
     }
 
+    /// Keep sensors polling while any auto-detection enabled
+    ///
+    /// Claiming and releasing is best-effort since service may be missing or outdated,
+    /// so detection keeps working with sensors polled by other clients.
+    async fn claim_orientation_poll(&self, enable: bool) {
+        if enable {
+            if let Err(error) = self.state.service.claim_orientation().await {
+                tracing::warn!("Error while claiming sensors polling: {error}");
+            }
+            return;
+        }
+
+        let (auto_tablet_mode, auto_orientation) = self
+            .with_config(|config| (config.tablet_mode.auto, config.orientation.auto))
+            .await;
+
+        if !auto_tablet_mode && !auto_orientation {
+            if let Err(error) = self.state.service.release_orientation().await {
+                tracing::warn!("Error while releasing sensors polling: {error}");
+            }
+        }
+    }
+
     async fn update_tablet_mode_detection(&self) -> Result<()> {
-        let iface = self.state.interface.read().await;
-        let sigctx = iface.as_ref().unwrap().signal_context();
+        {
+            let iface = self.state.interface.read().await;
+            let sigctx = iface.as_ref().unwrap().signal_context();
 
-        self.tablet_mode_detection_changed(sigctx).await?;
+            self.tablet_mode_detection_changed(sigctx).await?;
+        }
 
-        Ok(())
+        // detection may become available after sensors polling started
+        let auto = self.with_config(|config| config.tablet_mode.auto).await;
+        self.detect_tablet_mode(auto).await
     }
 
     async fn update_orientation_detection(&self) -> Result<()> {
-        let iface = self.state.interface.read().await;
-        let sigctx = iface.as_ref().unwrap().signal_context();
+        {
+            let iface = self.state.interface.read().await;
+            let sigctx = iface.as_ref().unwrap().signal_context();
 
-        self.orientation_detection_changed(sigctx).await?;
+            self.orientation_detection_changed(sigctx).await?;
+        }
 
-        Ok(())
+        // detection may become available after sensors polling started
+        let auto = self.with_config(|config| config.orientation.auto).await;
+        self.detect_orientation(auto).await
     }
 
     async fn monitor_service(&self, enable: bool) -> Result<()> {
@@ -10,10 +10,45 @@ pub struct Args {
     #[argp(option, short = 'c', arg_name = "path")]
     pub config: Option<PathBuf>,
 
+    /// Path to calibration state file.
+    #[argp(option, short = 'C', arg_name = "path")]
+    pub calibration: Option<PathBuf>,
+
+    /// Check config file and exit.
+    #[argp(switch)]
+    pub check_config: bool,
+
+    /// Print effective config with defaults and exit.
+    #[argp(switch)]
+    pub print_config: bool,
+
+    /// Print hardware diagnostic report and exit.
+    #[argp(switch)]
+    pub diagnose: bool,
+
+    /// Print diagnostic report in JSON instead of TOML.
+    #[argp(switch)]
+    pub json: bool,
+
     /// Run dbus service.
     #[argp(switch, short = 'd')]
     pub dbus: bool,
 
+    /// Record sensor trace to file.
+    #[cfg(any(feature = "input", feature = "iio"))]
+    #[argp(option, short = 'r', arg_name = "path")]
+    pub record: Option<PathBuf>,
+
+    /// Replay sensor trace from file and print detected changes.
+    #[cfg(feature = "iio")]
+    #[argp(option, short = 'p', arg_name = "path")]
+    pub replay: Option<PathBuf>,
+
+    /// Simulate sensors instead of using hardware.
+    #[cfg(feature = "iio")]
+    #[argp(switch, short = 's')]
+    pub simulate: bool,
+
     /// Logging filter.
     #[cfg(feature = "tracing-subscriber")]
     #[argp(
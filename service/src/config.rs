@@ -1,13 +1,36 @@
+#[cfg(feature = "iio")]
+use crate::Error;
 use crate::Result;
-use serde::Deserialize;
-use std::path::Path;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 /// Service configuration
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct Config {
     pub udev: Vec<UdevConfig>,
     pub device: Vec<DeviceConfig>,
     pub orientation: OrientationConfig,
+    #[serde(default)]
+    pub tablet_mode: TabletModeConfig,
+    #[serde(default)]
+    pub iio: IioConfig,
+    #[serde(default)]
+    pub sensor: Vec<SensorConfig>,
+    #[serde(default)]
+    pub calibration: CalibrationConfig,
+    /// Path to config file
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+    /// Path to calibration state file
+    #[serde(skip)]
+    pub calibration_path: Option<PathBuf>,
+    /// File which calibration is loaded from
+    #[serde(skip)]
+    calibration_source: Option<PathBuf>,
 }
 
 impl Default for Config {
@@ -15,10 +38,21 @@ impl Default for Config {
         let udev = Default::default();
         let device = Default::default();
         let orientation = Default::default();
+        let tablet_mode = Default::default();
+        let iio = Default::default();
+        let sensor = Default::default();
+        let calibration = Default::default();
         let mut cfg = Self {
             udev,
             device,
             orientation,
+            tablet_mode,
+            iio,
+            sensor,
+            calibration,
+            path: None,
+            calibration_path: None,
+            calibration_source: None,
         };
         cfg.validate();
         cfg
@@ -28,13 +62,290 @@ impl Default for Config {
 impl Config {
     /// Read config from file
     pub async fn from_file(path: impl AsRef<Path>) -> Result<Self> {
-        let raw = smol::fs::read(path).await?;
+        let raw = smol::fs::read(path.as_ref()).await?;
         let txt = core::str::from_utf8(&raw)?;
-        let mut cfg: Self = toml::from_str(txt)?;
+        let (mut cfg, unknown_keys) = Self::parse(txt)?;
+        // unknown keys is not fatal to keep service running with newer config
+        for problem in unknown_keys {
+            tracing::warn!("Config {:?}: {problem}", path.as_ref());
+        }
+        cfg.path = Some(path.as_ref().into());
+        if !cfg.calibration.is_empty() {
+            cfg.calibration_source = cfg.path.clone();
+        }
         cfg.validate();
         Ok(cfg)
     }
 
+    /// Load accelerometers calibration from state file
+    ///
+    /// Stored calibration overrides `[calibration]` section of config.
+    /// Missing file means that device is not calibrated yet.
+    pub async fn load_calibration(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        match smol::fs::read(path).await {
+            Ok(raw) => {
+                let txt = core::str::from_utf8(&raw)?;
+                let calibration: CalibrationConfig = toml::from_str(txt)?;
+                if !calibration.is_empty() {
+                    self.calibration = calibration;
+                    self.calibration_source = Some(path.into());
+                }
+            }
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+            Err(error) => return Err(error.into()),
+        }
+        self.calibration_path = Some(path.into());
+        Ok(())
+    }
+
+    /// Check that calibration state file can be stored
+    ///
+    /// Temporary file is created and removed in place of state file.
+    #[cfg(feature = "iio")]
+    pub async fn check_calibration_path(&self) -> Result<()> {
+        let path = self.calibration_path()?;
+        let tmp_path = tmp_path(path);
+        smol::fs::File::create(&tmp_path)
+            .await
+            .map_err(|error| Error::Calibration(format!("Unable to write {path:?}: {error}")))?;
+        smol::fs::remove_file(&tmp_path).await?;
+        Ok(())
+    }
+
+    /// Store accelerometers calibration to state file
+    ///
+    /// Calibration is written to temporary file first and then renamed over
+    /// state file, so it is never left partially written.
+    #[cfg(feature = "iio")]
+    pub async fn save_calibration(&self, calibration: &CalibrationConfig) -> Result<()> {
+        use smol::io::AsyncWriteExt;
+
+        let path = self.calibration_path()?;
+        let tmp_path = tmp_path(path);
+        let txt = toml::to_string_pretty(calibration)?;
+
+        let res = async {
+            let mut file = smol::fs::File::create(&tmp_path).await?;
+            file.write_all(txt.as_bytes()).await?;
+            file.sync_all().await?;
+            drop(file);
+            smol::fs::rename(&tmp_path, path).await
+        }
+        .await;
+
+        if let Err(error) = res {
+            let _ = smol::fs::remove_file(&tmp_path).await;
+            return Err(error.into());
+        }
+
+        tracing::info!("Calibration saved to {path:?}");
+
+        Ok(())
+    }
+
+    #[cfg(feature = "iio")]
+    fn calibration_path(&self) -> Result<&Path> {
+        self.calibration_path
+            .as_deref()
+            .ok_or_else(|| Error::Calibration("Calibration file not specified".into()))
+    }
+
+    /// Describe where accelerometers calibration comes from
+    pub fn calibration_origin(&self) -> String {
+        let calibrated = [
+            ("display", self.calibration.display.is_some()),
+            ("base", self.calibration.base.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(location, calibrated)| calibrated.then_some(location))
+        .collect::<Vec<_>>();
+
+        match (&self.calibration_source, calibrated.is_empty()) {
+            (_, true) => "not calibrated".into(),
+            (Some(path), _) => format!("{} from {path:?}", calibrated.join(", ")),
+            (None, _) => calibrated.join(", "),
+        }
+    }
+
+    /// Check config file
+    ///
+    /// Returns problems found, each prefixed by its location.
+    pub async fn check_file(path: impl AsRef<Path>) -> Result<Vec<String>> {
+        let raw = smol::fs::read(path.as_ref()).await?;
+        let txt = core::str::from_utf8(&raw)?;
+
+        Ok(Self::check_str(txt))
+    }
+
+    fn check_str(txt: &str) -> Vec<String> {
+        let (cfg, mut problems) = match Self::parse(txt) {
+            Ok(parsed) => parsed,
+            Err(error) => return vec![toml_problem(txt, &error)],
+        };
+
+        cfg.check_ranges(&mut problems);
+
+        problems
+    }
+
+    /// Parse config text
+    ///
+    /// Returns config and keys ignored while parsing, each prefixed by its location.
+    fn parse(txt: &str) -> core::result::Result<(Self, Vec<String>), toml::de::Error> {
+        let mut ignored = Vec::new();
+        let cfg = serde_ignored::deserialize(toml::Deserializer::new(txt), |path| {
+            let mut keys = Vec::new();
+            key_path(&path, &mut keys);
+            ignored.push(keys);
+        })?;
+
+        let input = toml_edit::ImDocument::parse(txt).ok();
+        let problems = ignored
+            .iter()
+            .map(|keys| {
+                let offset = input
+                    .as_ref()
+                    .and_then(|input| key_offset(input.as_table(), keys));
+                located(txt, offset, &format!("{}: unknown key", format_path(keys)))
+            })
+            .collect();
+
+        Ok((cfg, problems))
+    }
+
+    fn check_ranges(&self, problems: &mut Vec<String>) {
+        let mut check = |path: &str, value: f64, min: f64, max: f64| {
+            if !(min..=max).contains(&value) {
+                problems.push(format!("{path}: {value} is out of range {min}..{max}"));
+            }
+        };
+
+        let orientation = &self.orientation;
+        check(
+            "orientation.max_xy_angle",
+            orientation.max_xy_angle,
+            0.0,
+            90.0,
+        );
+        check(
+            "orientation.max_z_angle",
+            orientation.max_z_angle,
+            0.0,
+            90.0,
+        );
+        check("orientation.hysteresis", orientation.hysteresis, 0.0, 90.0);
+        check(
+            "orientation.max_velocity",
+            orientation.max_velocity,
+            f64::MIN_POSITIVE,
+            f64::MAX,
+        );
+        check(
+            "orientation.max_acceleration",
+            orientation.max_acceleration,
+            f64::MIN_POSITIVE,
+            f64::MAX,
+        );
+        check(
+            "orientation.stable_time",
+            orientation.stable_time,
+            0.0,
+            f64::MAX,
+        );
+        check(
+            "orientation.filter_time",
+            orientation.filter_time,
+            0.0,
+            f64::MAX,
+        );
+        check(
+            "orientation.process_noise",
+            orientation.process_noise,
+            f64::MIN_POSITIVE,
+            f64::MAX,
+        );
+        check(
+            "orientation.measurement_noise",
+            orientation.measurement_noise,
+            f64::MIN_POSITIVE,
+            f64::MAX,
+        );
+
+        let tablet_mode = &self.tablet_mode;
+        check(
+            "tablet_mode.hinge_angle",
+            tablet_mode.hinge_angle,
+            0.0,
+            360.0,
+        );
+        check(
+            "tablet_mode.hinge_hysteresis",
+            tablet_mode.hinge_hysteresis,
+            0.0,
+            360.0,
+        );
+        check(
+            "tablet_mode.min_hinge_tilt",
+            tablet_mode.min_hinge_tilt,
+            0.0,
+            90.0,
+        );
+        check(
+            "tablet_mode.closed_angle",
+            tablet_mode.closed_angle,
+            0.0,
+            360.0,
+        );
+        check(
+            "tablet_mode.folded_angle",
+            tablet_mode.folded_angle,
+            0.0,
+            360.0,
+        );
+
+        check(
+            "iio.sampling_frequency",
+            self.iio.sampling_frequency,
+            f64::MIN_POSITIVE,
+            f64::MAX,
+        );
+
+        for (index, sensor) in self.sensor.iter().enumerate() {
+            if let Some(mount_matrix) = &sensor.mount_matrix {
+                match parse_matrix(mount_matrix) {
+                    None => problems.push(format!(
+                        "sensor[{index}].mount_matrix: {mount_matrix:?} is not a matrix"
+                    )),
+                    Some(mat) if determinant(&mat).abs() < f64::EPSILON => problems.push(format!(
+                        "sensor[{index}].mount_matrix: {mount_matrix:?} is degenerate"
+                    )),
+                    _ => (),
+                }
+            }
+            if let Some(scale) = sensor.scale {
+                if scale.contains(&0.0) {
+                    problems.push(format!(
+                        "sensor[{index}].scale: {scale:?} should not contain zero"
+                    ));
+                }
+            }
+        }
+
+        if tablet_mode.hinge_hysteresis >= tablet_mode.hinge_angle {
+            problems.push(format!(
+                "tablet_mode.hinge_hysteresis: {} should be less than hinge_angle {}",
+                tablet_mode.hinge_hysteresis, tablet_mode.hinge_angle
+            ));
+        }
+        if tablet_mode.closed_angle >= tablet_mode.folded_angle {
+            problems.push(format!(
+                "tablet_mode.closed_angle: {} should be less than folded_angle {}",
+                tablet_mode.closed_angle, tablet_mode.folded_angle
+            ));
+        }
+    }
+
     fn validate(&mut self) {
         if self.udev.is_empty() {
             self.udev.push(UdevConfig::default());
@@ -42,8 +353,107 @@ impl Config {
     }
 }
 
+/// Path element of config key
+enum PathKey {
+    Key(String),
+    Index(usize),
+}
+
+/// Convert path of ignored field to keys
+fn key_path(path: &serde_ignored::Path, keys: &mut Vec<PathKey>) {
+    use serde_ignored::Path;
+
+    match path {
+        Path::Root => (),
+        Path::Seq { parent, index } => {
+            key_path(parent, keys);
+            keys.push(PathKey::Index(*index));
+        }
+        Path::Map { parent, key } => {
+            key_path(parent, keys);
+            keys.push(PathKey::Key(key.clone()));
+        }
+        Path::Some { parent }
+        | Path::NewtypeStruct { parent }
+        | Path::NewtypeVariant { parent } => key_path(parent, keys),
+    }
+}
+
+/// Format keys like `sensor[0].name`
+fn format_path(keys: &[PathKey]) -> String {
+    let mut path = String::new();
+    for key in keys {
+        match key {
+            PathKey::Key(key) => {
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(key);
+            }
+            PathKey::Index(index) => path.push_str(&format!("[{index}]")),
+        }
+    }
+    path
+}
+
+/// Find offset of key in config text
+fn key_offset(input: &dyn toml_edit::TableLike, keys: &[PathKey]) -> Option<usize> {
+    let (PathKey::Key(key), keys) = keys.split_first()? else {
+        return None;
+    };
+    if keys.is_empty() {
+        return input.key(key)?.span().map(|span| span.start);
+    }
+    let item = input.get(key)?;
+    match keys.split_first()? {
+        (PathKey::Index(index), keys) => match item {
+            toml_edit::Item::ArrayOfTables(input) => key_offset(input.get(*index)?, keys),
+            toml_edit::Item::Value(toml_edit::Value::Array(input)) => {
+                key_offset(input.get(*index)?.as_inline_table()?, keys)
+            }
+            _ => None,
+        },
+        _ => key_offset(item.as_table_like()?, keys),
+    }
+}
+
+/// Determinant of 3x3 matrix
+/// Temporary file path next to file
+#[cfg(feature = "iio")]
+fn tmp_path(path: &Path) -> PathBuf {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    tmp_path.into()
+}
+
+fn determinant(mat: &[[f64; 3]; 3]) -> f64 {
+    mat[0][0] * (mat[1][1] * mat[2][2] - mat[1][2] * mat[2][1])
+        - mat[0][1] * (mat[1][0] * mat[2][2] - mat[1][2] * mat[2][0])
+        + mat[0][2] * (mat[1][0] * mat[2][1] - mat[1][1] * mat[2][0])
+}
+
+/// Format parse error prefixed by its line and column
+fn toml_problem(txt: &str, error: &toml::de::Error) -> String {
+    located(
+        txt,
+        error.span().map(|span| span.start),
+        error.message().trim_end(),
+    )
+}
+
+/// Prefix message by line and column of text offset
+fn located(txt: &str, offset: Option<usize>, message: &str) -> String {
+    let Some(before) = offset.and_then(|offset| txt.get(..offset)) else {
+        return message.into();
+    };
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map(|pos| pos + 1).unwrap_or_default();
+    let column = before[line_start..].chars().count() + 1;
+    format!("line {line}, column {column}: {message}")
+}
+
 /// Service configuration
-#[derive(serde::Deserialize)]
+#[derive(serde::Serialize, serde::Deserialize)]
 pub struct UdevConfig {
     #[serde(default = "UdevConfig::default_seat")]
     pub seat: String,
@@ -64,22 +474,179 @@ impl UdevConfig {
 }
 
 /// Service configuration
-#[derive(serde::Deserialize)]
+#[derive(serde::Serialize, serde::Deserialize)]
 pub struct DeviceConfig {
     pub name: Option<String>,
     pub vid: Option<u32>,
     pub pid: Option<u32>,
     #[serde(default = "default_device_enable")]
     pub enable: bool,
+    /// Device is keyboard of detachable dock, tablet mode when detached
+    #[serde(default)]
+    pub dock: bool,
 }
 
 fn default_device_enable() -> bool {
     true
 }
 
+/// IIO sensors options
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IioConfig {
+    /// Read sensors using triggered buffer when supported
+    pub buffer: bool,
+    /// Buffered sampling frequency in Hz
+    pub sampling_frequency: f64,
+    /// Directory which mimics `/sys/bus/iio/devices` to read sensors from
+    pub sysfs_root: Option<PathBuf>,
+}
+
+impl Default for IioConfig {
+    fn default() -> Self {
+        Self {
+            buffer: true,
+            sampling_frequency: 10.0,
+            sysfs_root: None,
+        }
+    }
+}
+
+/// IIO sensor overrides
+///
+/// Device matches when all specified criteria matches.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SensorConfig {
+    /// Device name attribute to match
+    pub name: Option<String>,
+    /// Device sysfs path to match
+    pub syspath: Option<PathBuf>,
+    /// Device udev properties to match
+    #[serde(default)]
+    pub property: HashMap<String, String>,
+    #[serde(default = "default_device_enable")]
+    pub enable: bool,
+    /// Accelerometer location (`display` or `base`)
+    pub location: Option<AccelLocation>,
+    /// Mount matrix in form `x1, y1, z1; x2, y2, z2; x3, y3, z3`
+    pub mount_matrix: Option<String>,
+    /// Per-axis data offset added to raw value before scale
+    pub offset: Option<[f64; 3]>,
+    /// Per-axis data scale
+    pub scale: Option<[f64; 3]>,
+}
+
+/// Accelerometer location
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[repr(u8)]
+pub enum AccelLocation {
+    #[default]
+    #[serde(alias = "lid", alias = "accel-display")]
+    Display,
+    #[serde(alias = "accel-base")]
+    Base,
+}
+
+impl FromStr for AccelLocation {
+    type Err = ();
+    fn from_str(s: &str) -> core::result::Result<Self, Self::Err> {
+        Ok(match s {
+            "display" | "lid" | "accel-display" => Self::Display,
+            "base" | "accel-base" => Self::Base,
+            _ => return Err(()),
+        })
+    }
+}
+
+impl AsRef<str> for AccelLocation {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::Display => "display",
+            Self::Base => "base",
+        }
+    }
+}
+
+/// Parse matrix in form `x1, y1, z1; x2, y2, z2; x3, y3, z3`
+///
+/// Missing elements is zero.
+pub fn parse_matrix(s: &str) -> Option<[[f64; 3]; 3]> {
+    let mut mat = [[0f64; 3]; 3];
+
+    for (row, s) in s.split(';').enumerate() {
+        if row >= 3 {
+            break;
+        }
+        for (col, s) in s.split(',').enumerate() {
+            if col >= 3 {
+                break;
+            }
+            mat[row][col] = s.trim().parse().ok()?;
+        }
+    }
+
+    Some(mat)
+}
+
+/// Accelerometers calibration
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CalibrationConfig {
+    /// Display accelerometer corrections
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display: Option<AccelCalibration>,
+    /// Base accelerometer corrections
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base: Option<AccelCalibration>,
+}
+
+impl CalibrationConfig {
+    /// Whether no accelerometer is calibrated
+    pub fn is_empty(&self) -> bool {
+        self.display.is_none() && self.base.is_none()
+    }
+}
+
+/// Accelerometer corrections applied on top of sysfs offset and scale
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct AccelCalibration {
+    /// Per-axis offset in m/s^2 before mount matrix applied
+    pub offset: [f64; 3],
+    /// Per-axis scale
+    pub scale: [f64; 3],
+}
+
+/// Orientation detection backend
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OrientationBackend {
+    /// Read Industrial IO sensors directly
+    #[default]
+    Iio,
+    /// Subscribe to running iio-sensor-proxy
+    SensorProxy,
+}
+
+/// Accelerometer samples filter
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OrientationFilter {
+    /// Use samples as is
+    None,
+    /// Exponential moving average
+    #[default]
+    Ema,
+    /// Per-axis Kalman filter
+    Kalman,
+}
+
 /// Orientation detection options
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct OrientationConfig {
+    /// Source of orientation data
+    #[serde(default)]
+    pub backend: OrientationBackend,
     /// Plane XY angle tolerance in degrees
     pub max_xy_angle: f64,
     /// Plane Z angle tolerance in degrees
@@ -88,31 +655,89 @@ pub struct OrientationConfig {
     pub max_velocity: f64,
     /// Maximum allowed angular acceleration in degrees per second^2
     pub max_acceleration: f64,
+    /// Angle in degrees past 45° boundary to leave current orientation
+    ///
+    /// Matters only when `max_xy_angle` is greater than 45° minus hysteresis,
+    /// otherwise XY angle tolerance of new orientation is stricter.
+    #[serde(default = "OrientationConfig::default_hysteresis")]
+    pub hysteresis: f64,
+    /// Time in seconds new orientation should be held before switching
+    #[serde(default = "OrientationConfig::default_stable_time")]
+    pub stable_time: f64,
+    /// Accelerometer samples filter
+    #[serde(default)]
+    pub filter: OrientationFilter,
+    /// Moving average time constant in seconds
+    #[serde(default = "OrientationConfig::default_filter_time")]
+    pub filter_time: f64,
+    /// Kalman filter process noise variance per second
+    #[serde(default = "OrientationConfig::default_process_noise")]
+    pub process_noise: f64,
+    /// Kalman filter measurement noise variance
+    #[serde(default = "OrientationConfig::default_measurement_noise")]
+    pub measurement_noise: f64,
 }
 
 impl Default for OrientationConfig {
     fn default() -> Self {
         Self {
+            backend: OrientationBackend::default(),
             max_xy_angle: 20.0,
             max_z_angle: 60.0,
             max_velocity: 5.0,
             max_acceleration: 3.0,
+            hysteresis: Self::default_hysteresis(),
+            stable_time: Self::default_stable_time(),
+            filter: OrientationFilter::default(),
+            filter_time: Self::default_filter_time(),
+            process_noise: Self::default_process_noise(),
+            measurement_noise: Self::default_measurement_noise(),
         }
     }
 }
 
+#[cfg(feature = "iio")]
 const DEG_TO_RAD: f64 = core::f64::consts::PI / 180.0;
 
 impl OrientationConfig {
+    #[cfg(feature = "iio")]
     pub fn to_radians(&self) -> Self {
         Self {
+            backend: self.backend,
             max_xy_angle: self.max_xy_angle * DEG_TO_RAD,
             max_z_angle: self.max_z_angle * DEG_TO_RAD,
             max_velocity: self.max_velocity * DEG_TO_RAD,
             max_acceleration: self.max_acceleration * DEG_TO_RAD,
+            hysteresis: self.hysteresis * DEG_TO_RAD,
+            stable_time: self.stable_time,
+            filter: self.filter,
+            filter_time: self.filter_time,
+            process_noise: self.process_noise,
+            measurement_noise: self.measurement_noise,
         }
     }
 
+    fn default_hysteresis() -> f64 {
+        10.0
+    }
+
+    fn default_stable_time() -> f64 {
+        0.5
+    }
+
+    fn default_filter_time() -> f64 {
+        0.2
+    }
+
+    fn default_process_noise() -> f64 {
+        1.0
+    }
+
+    fn default_measurement_noise() -> f64 {
+        0.25
+    }
+
+    #[cfg(feature = "iio")]
     pub fn check(
         &self,
         xy_angle: Option<f64>,
@@ -134,3 +759,191 @@ impl OrientationConfig {
                 .unwrap_or_default()
     }
 }
+
+/// Tablet-mode detection options
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct TabletModeConfig {
+    /// Hinge angle in degrees above which device switches to tablet mode
+    pub hinge_angle: f64,
+    /// Hinge angle hysteresis in degrees below threshold to switch back to laptop mode
+    pub hinge_hysteresis: f64,
+    /// Minimum angle in degrees between hinge axis and gravity to trust readings
+    pub min_hinge_tilt: f64,
+    /// Hinge angle in degrees below which lid is considered closed
+    pub closed_angle: f64,
+    /// Hinge angle in degrees above which device is considered fully folded
+    pub folded_angle: f64,
+}
+
+impl Default for TabletModeConfig {
+    fn default() -> Self {
+        Self {
+            hinge_angle: 200.0,
+            hinge_hysteresis: 20.0,
+            min_hinge_tilt: 30.0,
+            closed_angle: 20.0,
+            folded_angle: 300.0,
+        }
+    }
+}
+
+impl TabletModeConfig {
+    #[cfg(feature = "iio")]
+    pub fn to_radians(&self) -> Self {
+        Self {
+            hinge_angle: self.hinge_angle * DEG_TO_RAD,
+            hinge_hysteresis: self.hinge_hysteresis * DEG_TO_RAD,
+            min_hinge_tilt: self.min_hinge_tilt * DEG_TO_RAD,
+            closed_angle: self.closed_angle * DEG_TO_RAD,
+            folded_angle: self.folded_angle * DEG_TO_RAD,
+        }
+    }
+
+    #[cfg(feature = "iio")]
+    pub fn check(&self, hinge_angle: f64, last_mode: Option<bool>) -> bool {
+        if last_mode.unwrap_or_default() {
+            hinge_angle > self.hinge_angle - self.hinge_hysteresis
+        } else {
+            hinge_angle > self.hinge_angle
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_problems() {
+        let problems = Config::check_str(
+            r#"
+udev = [{ set = "seat0" }]
+device = []
+
+[orientation]
+max_xy_angle = 20.0
+max_z_angle = 60.0
+max_velocity = 5.0
+max_acceleration = 3.0
+hysterisis = 10.0
+
+[tablet_mode]
+hinge_angle = 200.0
+hinge_hysteresis = 250.0
+
+[[sensor]]
+name = "accel_3d"
+locaton = "base"
+mount_matrix = "1, 0, 0; 0, 1, 0; 0, 0"
+scale = [0.01, 0.0, 0.01]
+"#,
+        );
+
+        assert_eq!(
+            problems,
+            [
+                "line 2, column 11: udev[0].set: unknown key",
+                "line 10, column 1: orientation.hysterisis: unknown key",
+                "line 18, column 1: sensor[0].locaton: unknown key",
+                "sensor[0].mount_matrix: \"1, 0, 0; 0, 1, 0; 0, 0\" is degenerate",
+                "sensor[0].scale: [0.01, 0.0, 0.01] should not contain zero",
+                "tablet_mode.hinge_hysteresis: 250 should be less than hinge_angle 200",
+            ]
+        );
+
+        let problems = Config::check_str(
+            r#"
+udev = []
+device = []
+
+[orientation]
+max_xy_angle = 20.0
+max_z_angle = 60.0
+max_velocity = 5.0
+max_acceleration = 3.0
+
+[[sensor]]
+name = "accel_3d"
+location = "keyboard"
+"#,
+        );
+
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("line 13, column 12: unknown variant `keyboard`"));
+
+        // only keys ignored by parsing is reported
+        let problems = Config::check_str(
+            r#"
+udev = []
+device = [{ name = "AT keyboard", enable = false }]
+
+[orientation]
+backend = "iio"
+max_xy_angle = 20.0
+max_z_angle = 60.0
+max_velocity = 5.0
+max_acceleration = 3.0
+
+[iio]
+sysfs_root = "/tmp/fake-iio"
+
+[[sensor]]
+syspath = "/sys/bus/iio/devices/iio:device0"
+property = { ID_PATH = "platform-HID-SENSOR-200073.1.auto" }
+
+[calibration]
+display = { offset = [0.0, 0.0, 0.0], scale = [1.0, 1.0, 1.0], gain = 1.0 }
+"#,
+        );
+
+        assert_eq!(
+            problems,
+            ["line 20, column 64: calibration.display.gain: unknown key"]
+        );
+    }
+
+    #[cfg(feature = "iio")]
+    #[test]
+    fn calibration_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("calibration.toml");
+
+        smol::block_on(async {
+            let mut config = Config::default();
+            assert!(config.check_calibration_path().await.is_err());
+
+            config.load_calibration(&path).await.unwrap();
+            assert_eq!(config.calibration_origin(), "not calibrated");
+            config.check_calibration_path().await.unwrap();
+
+            let calibration = CalibrationConfig {
+                display: Some(AccelCalibration {
+                    offset: [0.1, -0.2, 0.3],
+                    scale: [1.0, 1.01, 0.99],
+                }),
+                base: None,
+            };
+            config.save_calibration(&calibration).await.unwrap();
+
+            // only state file is left in directory
+            let files = std::fs::read_dir(dir.path()).unwrap().count();
+            assert_eq!(files, 1);
+
+            let mut config = Config::default();
+            config.load_calibration(&path).await.unwrap();
+            let display = config.calibration.display.unwrap();
+            assert_eq!(display.offset, [0.1, -0.2, 0.3]);
+            assert_eq!(display.scale, [1.0, 1.01, 0.99]);
+            assert!(config.calibration.base.is_none());
+            assert_eq!(
+                config.calibration_origin(),
+                format!("display from {path:?}")
+            );
+
+            // directory which is not writable is reported before sampling
+            config.calibration_path = Some(dir.path().join("missing").join("calibration.toml"));
+            assert!(config.check_calibration_path().await.is_err());
+        });
+    }
+}
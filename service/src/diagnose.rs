@@ -0,0 +1,133 @@
+#[cfg(feature = "iio")]
+use crate::Orientation;
+use crate::{Config, Result};
+use serde::Serialize;
+use std::path::PathBuf;
+#[cfg(feature = "iio")]
+use std::time::Duration;
+
+/// Duration of accelerometers sampling
+#[cfg(feature = "iio")]
+pub const DIAGNOSE_DURATION: Duration = Duration::from_secs(3);
+
+/// DMI identification path
+const DMI_PATH: &str = "/sys/class/dmi/id";
+
+/// Hardware diagnostic report
+#[derive(Serialize)]
+pub struct Report {
+    /// Device identification
+    pub dmi: DmiReport,
+    /// Input switch devices
+    #[cfg(feature = "input")]
+    pub input: Vec<InputDeviceReport>,
+    /// Industrial IO devices
+    #[cfg(feature = "iio")]
+    pub iio: Vec<IioDeviceReport>,
+    /// Accelerometer samples
+    #[cfg(feature = "iio")]
+    pub sample: Vec<AccelSampleReport>,
+}
+
+/// Device identification from DMI
+#[derive(Serialize)]
+pub struct DmiReport {
+    pub sys_vendor: Option<String>,
+    pub product_name: Option<String>,
+    pub product_version: Option<String>,
+    pub product_family: Option<String>,
+    pub board_vendor: Option<String>,
+    pub board_name: Option<String>,
+    pub chassis_type: Option<String>,
+}
+
+/// Input device with switches
+#[cfg(feature = "input")]
+#[derive(Serialize)]
+pub struct InputDeviceReport {
+    pub sysname: String,
+    pub name: String,
+    pub vid: u32,
+    pub pid: u32,
+    /// Device capabilities like `keyboard` or `switch`
+    pub capabilities: Vec<String>,
+    pub tablet_mode_switch: bool,
+    pub lid_switch: bool,
+    /// Whether device used as dock keyboard according to config
+    pub dock: bool,
+}
+
+/// Industrial IO device
+#[cfg(feature = "iio")]
+#[derive(Serialize)]
+pub struct IioDeviceReport {
+    pub syspath: PathBuf,
+    pub name: Option<String>,
+    pub label: Option<String>,
+    /// Sensor types like `accel` or `light`
+    pub sensors: Vec<String>,
+    /// Accelerometer location (`display` or `base`)
+    pub location: Option<String>,
+    /// Mount matrix columns, same groups as in `mount_matrix` config option
+    pub mount_matrix: Option<[[f64; 3]; 3]>,
+    pub scale: Option<[f64; 3]>,
+    pub offset: Option<[f64; 3]>,
+}
+
+/// Accelerometer sample with computed orientation
+#[cfg(feature = "iio")]
+#[derive(Serialize)]
+pub struct AccelSampleReport {
+    /// Time since sampling start in seconds
+    pub time: f64,
+    /// Accelerometer location (`display` or `base`)
+    pub location: String,
+    /// Raw channel values
+    pub raw: [f64; 3],
+    /// Value in device frame, m/s^2
+    pub value: [f64; 3],
+    pub orientation: Orientation,
+    /// Plane XY angle in degrees
+    pub xy_angle: f64,
+    /// Plane Z angle in degrees
+    pub z_angle: f64,
+}
+
+impl Report {
+    /// Collect report using sensors directly
+    #[cfg_attr(not(any(feature = "input", feature = "iio")), allow(unused_variables))]
+    pub async fn collect(config: &Config) -> Result<Self> {
+        #[cfg(feature = "iio")]
+        let iio_devices = config.find_iio_devices()?;
+
+        Ok(Self {
+            dmi: DmiReport::collect(),
+            #[cfg(feature = "input")]
+            input: config.find_input_devices()?,
+            #[cfg(feature = "iio")]
+            iio: config.describe_iio_devices(&iio_devices),
+            #[cfg(feature = "iio")]
+            sample: crate::Iio::sample_orientation(&iio_devices, config, DIAGNOSE_DURATION).await?,
+        })
+    }
+}
+
+impl DmiReport {
+    fn collect() -> Self {
+        let read = |name: &str| {
+            std::fs::read_to_string(PathBuf::from(DMI_PATH).join(name))
+                .ok()
+                .map(|value| value.trim().to_string())
+        };
+
+        Self {
+            sys_vendor: read("sys_vendor"),
+            product_name: read("product_name"),
+            product_version: read("product_version"),
+            product_family: read("product_family"),
+            board_vendor: read("board_vendor"),
+            board_name: read("board_name"),
+            chassis_type: read("chassis_type"),
+        }
+    }
+}
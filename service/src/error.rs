@@ -25,9 +25,25 @@ pub enum Error {
     /// TOML formatting error
     #[error("TOML serialize error: {0}")]
     TomlSer(#[from] toml::ser::Error),
+    /// TOML editing error
+    #[error("TOML edit error: {0}")]
+    TomlEdit(#[from] toml_edit::TomlError),
+    /// JSON formatting error
+    #[error("JSON serialize error: {0}")]
+    Json(#[from] serde_json::Error),
     /// Termination error
     #[error("Unexpected termination")]
     Term,
+    /// Calibration error
+    #[error("Calibration error: {0}")]
+    Calibration(String),
+    /// Config checking error
+    #[error("Config error: {0}")]
+    Config(String),
+    #[cfg(feature = "iio")]
+    /// Trace parsing error
+    #[error("Trace error: {0}")]
+    Trace(String),
     #[cfg(feature = "input")]
     /// Input subsystem error
     #[error("Input error: {0}")]
@@ -48,7 +64,13 @@ impl AsRef<str> for Error {
             Self::Utf8(_) => "utf8",
             Self::TomlDe(_) => "toml-de",
             Self::TomlSer(_) => "toml-ser",
+            Self::TomlEdit(_) => "toml-edit",
+            Self::Json(_) => "json",
             Self::Term => "term",
+            Self::Calibration(_) => "calibration",
+            Self::Config(_) => "config",
+            #[cfg(feature = "iio")]
+            Self::Trace(_) => "trace",
             #[cfg(feature = "input")]
             Self::Input(e) => e.as_ref(),
             #[cfg(feature = "iio")]
@@ -57,16 +79,86 @@ impl AsRef<str> for Error {
     }
 }
 
+/// Prefix of D-Bus error names
+const ERROR_NAME_PREFIX: &str = "tablet.assist.Error";
+
 impl zbus::DBusError for Error {
     fn create_reply(&self, msg: &zbus::MessageHeader<'_>) -> zbus::Result<zbus::Message> {
         zbus::MessageBuilder::error(msg, self.name())?.build(&self.to_string())
     }
 
     fn name(&self) -> zbus::names::ErrorName<'_> {
-        zbus::names::ErrorName::from_str_unchecked(self.as_ref())
+        if let Self::DBusFdo(error) = self {
+            return zbus::DBusError::name(error);
+        }
+
+        // kind like `toml-de` converted to name like `tablet.assist.Error.TomlDe`
+        let mut name = String::from(ERROR_NAME_PREFIX);
+        name.push('.');
+        for word in self.as_ref().split('-') {
+            let mut chars = word.chars();
+            if let Some(first) = chars.next() {
+                name.extend(first.to_uppercase());
+                name.extend(chars);
+            }
+        }
+
+        zbus::names::ErrorName::from_string_unchecked(name)
     }
 
     fn description(&self) -> Option<&str> {
         Some(self.as_ref())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zbus::{names::ErrorName, DBusError};
+
+    #[test]
+    fn dbus_error_names() {
+        let invalid_utf8 = vec![0xff];
+        let _ =
+            tracing::subscriber::set_global_default(tracing::subscriber::NoSubscriber::default());
+
+        let errors = [
+            Error::Io(std::io::Error::other("io")),
+            Error::DBus(zbus::Error::Failure("dbus".into())),
+            Error::DBusFdo(zbus::fdo::Error::NotSupported("fdo".into())),
+            Error::Tracing(
+                tracing::subscriber::set_global_default(
+                    tracing::subscriber::NoSubscriber::default(),
+                )
+                .unwrap_err(),
+            ),
+            Error::Utf8(core::str::from_utf8(&invalid_utf8).unwrap_err()),
+            Error::TomlDe(toml::from_str::<toml::Table>("=").unwrap_err()),
+            Error::TomlSer(toml::to_string(&1).unwrap_err()),
+            Error::TomlEdit("=".parse::<toml_edit::DocumentMut>().unwrap_err()),
+            Error::Json(serde_json::from_str::<u8>("-").unwrap_err()),
+            Error::Term,
+            Error::Calibration("calibration".into()),
+            Error::Config("config".into()),
+            #[cfg(feature = "iio")]
+            Error::Trace("trace".into()),
+            #[cfg(feature = "input")]
+            Error::Input(crate::InputError::AddSeat("seat0".into())),
+            #[cfg(feature = "iio")]
+            Error::Iio(crate::IioError::Poll("poll".into())),
+            #[cfg(feature = "iio")]
+            Error::Iio(crate::IioError::Buffer("buffer".into())),
+        ];
+
+        for error in &errors {
+            let name = error.name().to_string();
+            assert!(ErrorName::try_from(name.as_str()).is_ok(), "{name}");
+        }
+
+        assert_eq!(errors[5].name().as_str(), "tablet.assist.Error.TomlDe");
+        assert_eq!(
+            errors[2].name().as_str(),
+            "org.freedesktop.DBus.Error.NotSupported"
+        );
+    }
+}
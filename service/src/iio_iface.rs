@@ -1,32 +1,102 @@
-use crate::{Config, Orientation, OrientationConfig, Result, Service};
-use core::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+use crate::{
+    AccelCalibration, AccelData, AccelLocation, AccelSampleReport, CalibrationConfig,
+    CalibrationPose, CalibrationRequest, Claim, Config, Error, IioConfig, IioDeviceReport,
+    Orientation, OrientationConfig, OrientationFilter, Posture, Recorder, Result, SensorConfig,
+    SensorState, Service, TabletModeConfig, TabletModeSource, TraceEvent, TraceReader, TraceSwitch,
+};
+use core::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI, TAU};
 use glam::{dvec3 as vec3, DMat3 as Mat3, DVec2 as Vec2, DVec3 as Vec3};
+use smol::{future::FutureExt, Async};
 use std::{
+    collections::VecDeque,
     ffi::OsStr,
+    fs::File,
     path::{Path, PathBuf},
     str::FromStr,
     time::{Duration, Instant},
 };
 
+mod fake;
+
+use fake::FakeDevice;
+
 /// IIO error type
 #[derive(thiserror::Error, Debug)]
 pub enum IioError {
     /// Poll error
     #[error("Poll sensor: {0}")]
     Poll(String),
+    /// Buffer error
+    #[error("Sensor buffer: {0}")]
+    Buffer(String),
 }
 
 impl AsRef<str> for IioError {
     fn as_ref(&self) -> &str {
         match self {
             Self::Poll(_) => "iio-poll",
+            Self::Buffer(_) => "iio-buffer",
         }
     }
 }
 
 impl Config {
+    /// Describe IIO devices for diagnostic report
+    pub fn describe_iio_devices(&self, paths: &[PathBuf]) -> Vec<IioDeviceReport> {
+        paths
+            .iter()
+            .filter_map(|path| match IioDevice::from_syspath(path, &self.iio) {
+                Ok(device) => Some(device),
+                Err(error) => {
+                    tracing::warn!("Unable to open IIO device {path:?}: {error}");
+                    None
+                }
+            })
+            .map(|device| {
+                let types = device.sensor_types();
+                let accel = types.iter().any(|type_| matches!(type_, SensorType::Accel));
+                let (mount, scale, offset) = if accel {
+                    (
+                        device.accel_mount_matrix(),
+                        device.accel_scale(),
+                        device.accel_offset(),
+                    )
+                } else {
+                    (
+                        device.magn_mount_matrix(),
+                        device.magn_scale(),
+                        device.magn_offset(),
+                    )
+                };
+                IioDeviceReport {
+                    syspath: device.syspath().into(),
+                    name: device.attribute_value_str("name").map(String::from),
+                    label: device.attribute_value_str("label").map(String::from),
+                    sensors: types.iter().map(|type_| type_.as_ref().into()).collect(),
+                    location: device
+                        .accel_location()
+                        .filter(|_| accel)
+                        .map(|location| location.as_ref().into()),
+                    mount_matrix: mount.map(|mount| mount.to_cols_array_2d()),
+                    scale: scale.map(|scale| scale.to_array()),
+                    offset: offset.map(|offset| offset.to_array()),
+                }
+            })
+            .collect()
+    }
+
     #[cfg(feature = "iio")]
     pub fn find_iio_devices(&self) -> Result<Vec<PathBuf>> {
+        if let Some(root) = &self.iio.sysfs_root {
+            let mut devices = Vec::new();
+            for path in FakeDevice::enumerate(root)? {
+                if !FakeDevice::from_syspath(&path)?.sensor_types().is_empty() {
+                    devices.push(path);
+                }
+            }
+            return Ok(devices);
+        }
+
         let mut enumerator = udev::Enumerator::new()?;
 
         enumerator.match_subsystem("iio")?;
@@ -34,7 +104,7 @@ impl Config {
         let devices = enumerator
             .scan_devices()
             .unwrap()
-            .filter(|dev| dev.is_initialized() && dev.sensor_type().is_some())
+            .filter(|dev| dev.is_initialized() && !dev.sensor_types().is_empty())
             .map(|drv| drv.syspath().into())
             .collect();
 
@@ -42,348 +112,2453 @@ impl Config {
     }
 }
 
+/// Minimum hinge angle change in degrees to report
+const HINGE_ANGLE_STEP: f64 = 1.0;
+
+/// Sensors polling interval when buffer is not supported
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Time window to estimate angular velocity and acceleration over
+///
+/// Keeps orientation thresholds independent of sampling frequency.
+const DERIVATIVE_WINDOW: Duration = Duration::from_secs(1);
+
+/// Accelerometer channels
+const ACCEL_CHANNELS: &[&str] = &["in_accel_x", "in_accel_y", "in_accel_z"];
+
+/// Illuminance channel candidates
+const LIGHT_CHANNELS: &[&str] = &["in_illuminance", "in_illuminance0"];
+
+/// Magnetometer channels
+const MAGN_CHANNELS: &[&str] = &["in_magn_x", "in_magn_y", "in_magn_z"];
+
+/// Compass heading channel
+const HEADING_CHANNEL: &str = "in_rot_from_north_magnetic_tilt_comp";
+
+/// Minimum compass heading change in degrees to report
+const HEADING_STEP: f64 = 1.0;
+
+/// Proximity channel candidates
+const PROXIMITY_CHANNELS: &[&str] = &["in_proximity", "in_proximity0"];
+
+/// Sampling duration for each calibration pose
+const CALIBRATION_DURATION: Duration = Duration::from_secs(2);
+
+/// Maximum angle between base and display gravity vectors while calibrating, degrees
+const CALIBRATION_MAX_DISAGREEMENT: f64 = 15.0;
+
+/// Maximum relative deviation of measured gravity from standard one while calibrating
+const CALIBRATION_MAX_GRAVITY_ERROR: f64 = 0.25;
+
+/// Standard gravity, m/s^2
+const STANDARD_GRAVITY: f64 = 9.80665;
+
+/// Minimum light level change in lux to report
+const LIGHT_LEVEL_STEP: f64 = 1.0;
+
+/// Minimum relative light level change to report
+const LIGHT_LEVEL_RATIO: f64 = 0.1;
+
 #[derive(Default)]
 pub struct Iio {
     display_accel: Option<Accel>,
     base_accel: Option<Accel>,
+    light: Option<Light>,
+    proximity: Option<Proximity>,
+    compass: Option<Compass>,
+    /// Accelerometers which location already occupied
+    spare_accels: Vec<Accel>,
+    /// Sensors claimed by clients
+    claims: Vec<Claim>,
     orientation_config: OrientationConfig,
+    iio_config: IioConfig,
+    /// Sensor overrides
+    sensors: Vec<SensorConfig>,
+    tablet_mode_config: TabletModeConfig,
+    /// Current accelerometers calibration
+    calibration: CalibrationConfig,
+    /// Mean sensor values sampled in calibration poses
+    calibration_samples: Vec<(AccelLocation, CalibrationPose, Vec3)>,
+    /// Detected display orientation
+    orientation: Option<Orientation>,
+    /// Orientation candidate with sample time when it was detected first
+    pending_orientation: Option<(Orientation, Duration)>,
+    /// Tracked hinge angle, rad
+    hinge_angle: Option<f64>,
+    /// Detected tablet mode
+    tablet_mode: Option<bool>,
+    /// Sensor trace writer
+    recorder: Option<Recorder>,
 }
 
 impl Iio {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            orientation_config: config.orientation.to_radians(),
+            tablet_mode_config: config.tablet_mode.to_radians(),
+            iio_config: config.iio.clone(),
+            sensors: config.sensor.clone(),
+            calibration: config.calibration.clone(),
+            ..Self::default()
+        }
+    }
+
     pub fn from_paths(
         paths: impl IntoIterator<Item = impl AsRef<Path>>,
-        orientation_config: &OrientationConfig,
+        config: &Config,
     ) -> Result<Self> {
-        let mut iio = Self {
-            orientation_config: orientation_config.to_radians(),
-            ..Self::default()
-        };
+        let mut iio = Self::new(config);
 
         for path in paths {
-            let device = udev::Device::from_syspath(path.as_ref())?;
-            if let Some(SensorType::Accel) = device.sensor_type() {
-                let accel = Accel::new(device)?;
-                tracing::info!("Use device: {accel:?}");
-                match accel.location {
-                    AccelLocation::Display => iio.display_accel = accel.into(),
-                    AccelLocation::Base => iio.base_accel = accel.into(),
-                }
-            }
+            iio.add_device(IioDevice::from_syspath(path, &iio.iio_config)?)?;
         }
 
         Ok(iio)
     }
 
-    pub fn poll(&mut self) -> Result<()> {
-        if let Some(accel) = &mut self.display_accel {
-            accel.poll()?;
-        }
-        if let Some(accel) = &mut self.base_accel {
-            accel.poll()?;
+    /// Sample accelerometers for diagnostic report
+    pub async fn sample_orientation(
+        paths: &[PathBuf],
+        config: &Config,
+        duration: Duration,
+    ) -> Result<Vec<AccelSampleReport>> {
+        let mut iio = Self::from_paths(paths, config)?;
+        let mut samples = Vec::new();
+
+        iio.set_active(Claim::Orientation, true);
+
+        let start = Instant::now();
+        while start.elapsed() < duration {
+            iio.poll()?;
+
+            for accel in iio.accels() {
+                let (Some(raw), Some(value), Some((orientation, z_angle, xy_angle))) =
+                    (accel.raw, accel.value(), accel.plane_orientation())
+                else {
+                    continue;
+                };
+                samples.push(AccelSampleReport {
+                    time: start.elapsed().as_secs_f64(),
+                    location: accel.location.as_ref().into(),
+                    raw: raw.to_array(),
+                    value: value.to_array(),
+                    orientation,
+                    xy_angle: xy_angle.to_degrees(),
+                    z_angle: z_angle.to_degrees(),
+                });
+            }
+
+            if !iio.is_active() {
+                break;
+            }
+
+            iio.wait()
+                .or(async {
+                    smol::Timer::after(duration.saturating_sub(start.elapsed())).await;
+                })
+                .await;
         }
-        Ok(())
-    }
 
-    pub fn display_orientation(&self) -> Option<Orientation> {
-        self.display_accel
-            .as_ref()
-            .and_then(|accel| accel.plane_orientation_checked(&self.orientation_config))
+        Ok(samples)
     }
 
-    pub fn tablet_mode(&self) -> Option<bool> {
-        self.base_accel
-            .as_ref()
-            .and_then(|accel| accel.value())
-            .and_then(|base| {
-                self.display_accel
-                    .as_ref()
-                    .and_then(|accel| accel.value())
-                    .map(|display| base.angle_between(*display))
-            })
-            .map(|angle| angle < FRAC_PI_2)
-        // TODO:
+    /// Record accelerometer samples to trace
+    pub fn set_recorder(&mut self, recorder: Option<Recorder>) {
+        self.recorder = recorder;
     }
 
-    pub async fn process(
-        devices: Vec<PathBuf>,
-        service: Service,
-        orientation_config: &OrientationConfig,
-    ) -> Result<Option<async_signal::Signal>> {
-        let mut iio = Self::from_paths(devices, orientation_config)?;
-        let mut last_display_orient = None;
-        let mut last_tablet_mode = None;
-
-        loop {
-            let timer = smol::Timer::after(Duration::from_secs(1));
+    /// Feed recorded trace through sensors pipeline
+    ///
+    /// The changes detected from recorded samples written to output in trace format.
+    /// Samples processed as fast as possible regardless of recorded time.
+    pub fn replay(
+        path: impl AsRef<Path>,
+        config: &Config,
+        mut output: impl std::io::Write,
+    ) -> Result<()> {
+        let mut iio = Self::new(config);
+        let mut detections = Detections::default();
+        let mut lid_closed = false;
 
-            if let Err(error) = iio.poll() {
-                tracing::warn!("Error while polling IIO sensors: {error}");
-            }
+        for entry in TraceReader::open(path)? {
+            let (time, event) = entry?;
 
-            if let Some(orient) = iio.display_orientation() {
-                if !last_display_orient
-                    .map(|last_orient| last_orient == orient)
-                    .unwrap_or_default()
-                {
-                    tracing::debug!("Detected orientation change: {orient:?}");
-                    last_display_orient = orient.into();
-                    if let Err(error) = service.set_orientation(orient).await {
-                        tracing::warn!("Error while setting orientation: {error}");
-                    }
+            match event {
+                TraceEvent::Sensor {
+                    location,
+                    mount,
+                    offset,
+                    scale,
+                } => {
+                    iio.replay_accel(
+                        location,
+                        Mat3::from_cols_array_2d(&mount),
+                        Vec3::from_array(offset),
+                        Vec3::from_array(scale),
+                    );
+                    continue;
                 }
+                TraceEvent::Accel {
+                    location,
+                    time,
+                    raw,
+                } => iio.push_raw_accel(location, raw, time),
+                TraceEvent::Switch {
+                    switch: TraceSwitch::Lid,
+                    state,
+                } => lid_closed = state,
+                // recorded decisions is what replay reproduces
+                _ => continue,
             }
 
-            if let Some(mode) = iio.tablet_mode() {
-                if !last_tablet_mode
-                    .map(|last_mode| last_mode == mode)
-                    .unwrap_or_default()
-                {
-                    tracing::debug!("Detected tablet-mode change: {mode:?}");
-                    last_tablet_mode = mode.into();
-                    if let Err(error) = service.set_tablet_mode(mode).await {
-                        tracing::warn!("Error while setting tablet mode: {error}");
-                    }
+            for detection in detections.update(&iio, lid_closed) {
+                if let Some(event) = detection.trace_event() {
+                    crate::write_trace(&mut output, time, &event)?;
                 }
             }
-
-            timer.await;
         }
+
+        Ok(())
     }
-}
 
-#[derive(Clone, Copy, Debug, Default)]
-#[repr(u8)]
-enum AccelLocation {
-    #[default]
-    Display,
-    Base,
-}
+    /// Push accelerometer sample which is not read from device
+    ///
+    /// The value is in device frame, m/s^2. Sensor is created on first sample.
+    pub fn push_accel(&mut self, location: AccelLocation, value: [f64; 3], time: Duration) {
+        let filter = Filter::new(&self.orientation_config);
+        let slot = match location {
+            AccelLocation::Display => &mut self.display_accel,
+            AccelLocation::Base => &mut self.base_accel,
+        };
+        slot.get_or_insert_with(|| Accel::replay(location, filter))
+            .push(Vec3::from_array(value), time);
+        self.update_orientation();
+        self.update_hinge_angle();
+    }
 
-impl FromStr for AccelLocation {
-    type Err = ();
-    fn from_str(s: &str) -> core::result::Result<Self, Self::Err> {
-        Ok(match s {
-            "display" | "lid" | "accel-display" => Self::Display,
-            "base" | "accel-base" => Self::Base,
-            _ => return Err(()),
-        })
+    /// Use sensor which samples is pushed from trace
+    ///
+    /// The parameters is recorded from device, calibration is taken from config.
+    pub fn replay_accel(
+        &mut self,
+        location: AccelLocation,
+        mount: Mat3,
+        offset: Vec3,
+        scale: Vec3,
+    ) {
+        let mut accel = Accel::replay(location, Filter::new(&self.orientation_config));
+        accel.mount = mount;
+        accel.offset = offset;
+        accel.scale = scale;
+        let (slot, calibration) = match location {
+            AccelLocation::Display => (&mut self.display_accel, self.calibration.display),
+            AccelLocation::Base => (&mut self.base_accel, self.calibration.base),
+        };
+        if let Some(calibration) = calibration {
+            accel.set_calibration(&calibration);
+        }
+        *slot = accel.into();
     }
-}
 
-#[derive(Debug)]
-struct Accel {
-    /// Associated device
-    device: udev::Device,
-    /// Sensor location
-    location: AccelLocation,
-    /// Sensor mount matrix
-    mount: Mat3,
-    /// Sensor data offset
-    offset: Vec3,
-    /// Sensor data scale
-    scale: Vec3,
-    /// Latest data with time
-    record: Option<(Vec3, Instant)>,
-    /// Angular velocity, rad/sec
-    velocity: Option<f64>,
-    /// Angular acceleration, rad/sec^2
-    acceleration: Option<f64>,
-}
+    /// Push raw accelerometer sample from trace
+    ///
+    /// Sensor without recorded parameters is created on first sample.
+    pub fn push_raw_accel(&mut self, location: AccelLocation, raw: [f64; 3], time: Duration) {
+        let has_accel = match location {
+            AccelLocation::Display => self.display_accel.is_some(),
+            AccelLocation::Base => self.base_accel.is_some(),
+        };
+        if !has_accel {
+            self.replay_accel(location, Mat3::IDENTITY, Vec3::ZERO, Vec3::ONE);
+        }
+        let slot = match location {
+            AccelLocation::Display => &mut self.display_accel,
+            AccelLocation::Base => &mut self.base_accel,
+        };
+        if let Some(accel) = slot {
+            accel.push_raw(Vec3::from_array(raw), time);
+        }
+        self.update_orientation();
+        self.update_hinge_angle();
+    }
 
-impl Accel {
-    pub fn new(device: udev::Device) -> Result<Self> {
-        let location = device.accel_location().unwrap_or_default();
-        let mount = device.accel_mount_matrix().unwrap_or(Mat3::IDENTITY);
-        let offset = device.accel_offset().unwrap_or(Vec3::ZERO);
-        let scale = device.accel_scale().unwrap_or(Vec3::ONE);
+    /// Start using sensors provided by device
+    fn add_device(&mut self, device: IioDevice) -> Result<()> {
+        if self.has_device(device.syspath()) {
+            return Ok(());
+        }
 
-        Ok(Self {
-            device,
-            location,
-            mount,
-            offset,
-            scale,
-            record: Default::default(),
-            velocity: Default::default(),
-            acceleration: Default::default(),
-        })
-    }
+        let config = &self.iio_config;
+        let types = device.sensor_types();
+        // single buffer cannot be shared by several sensors
+        let buffered = types.len() == 1;
+
+        for type_ in types {
+            match type_ {
+                SensorType::Accel => {
+                    let sensor = self.sensors.iter().find(|sensor| sensor.matches(&device));
+                    if sensor.map(|sensor| !sensor.enable).unwrap_or_default() {
+                        tracing::info!("Skip disabled accelerometer: {:?}", device.syspath());
+                        continue;
+                    }
+                    let mut accel = Accel::new(device.clone(), buffered, config, sensor)?;
+                    let calibration = match accel.location {
+                        AccelLocation::Display => self.calibration.display,
+                        AccelLocation::Base => self.calibration.base,
+                    };
+                    if let Some(calibration) = calibration {
+                        accel.set_calibration(&calibration);
+                    }
+                    accel.set_filter(Filter::new(&self.orientation_config));
+                    let slot = match accel.location {
+                        AccelLocation::Display => &mut self.display_accel,
+                        AccelLocation::Base => &mut self.base_accel,
+                    };
+                    if slot.is_none() {
+                        tracing::info!("Use device: {accel:?}");
+                        *slot = accel.into();
+                    } else {
+                        tracing::info!("Keep spare device: {accel:?}");
+                        self.spare_accels.push(accel);
+                    }
+                }
+                SensorType::Light => {
+                    let light = Light::new(device.clone(), buffered, config)?;
+                    tracing::info!("Use device: {light:?}");
+                    self.light = light.into();
+                }
+                SensorType::Compass => {
+                    let compass = Compass::new(device.clone(), buffered, config)?;
+                    tracing::info!("Use device: {compass:?}");
+                    self.compass = compass.into();
+                }
+                SensorType::Proximity => match Proximity::new(device.clone(), buffered, config) {
+                    Ok(proximity) => {
+                        tracing::info!("Use device: {proximity:?}");
+                        self.proximity = proximity.into();
+                    }
+                    Err(error) => {
+                        tracing::warn!("Skip proximity device {:?}: {error}", device.syspath());
+                    }
+                },
+            }
+        }
 
-    pub fn poll(&mut self) -> Result<()> {
-        let time = Instant::now();
-        let raw = self
-            .device
-            .accel_raw()
-            .ok_or_else(|| IioError::Poll("accel".into()))?;
-        let val = (raw - self.offset) * self.scale;
-        let val = self.mount * val;
-        self.push(val, time);
         Ok(())
     }
 
-    fn push(&mut self, value: Vec3, time: Instant) {
-        if let Some((had_value, had_time)) = self.record.replace((value, time)) {
-            let delta_time = (time - had_time).as_secs_f64();
-            let velocity = value.angle_between(had_value) / delta_time;
-            if let Some(had_velocity) = self.velocity.replace(velocity) {
-                let acceleration = (velocity - had_velocity) / delta_time;
-                self.acceleration.replace(acceleration);
+    /// Stop using sensors provided by removed device
+    pub fn remove_device(&mut self, syspath: &Path) {
+        let matches = |reader: &IioReader| reader.device.syspath() == syspath;
+
+        if self
+            .display_accel
+            .as_ref()
+            .and_then(|accel| accel.reader.as_ref())
+            .map(matches)
+            == Some(true)
+        {
+            tracing::info!("Drop display accelerometer: {syspath:?}");
+            self.display_accel = None;
+            self.orientation = None;
+            self.pending_orientation = None;
+        }
+        if self
+            .base_accel
+            .as_ref()
+            .and_then(|accel| accel.reader.as_ref())
+            .map(matches)
+            == Some(true)
+        {
+            tracing::info!("Drop base accelerometer: {syspath:?}");
+            self.base_accel = None;
+        }
+        if self.light.as_ref().map(|light| matches(&light.reader)) == Some(true) {
+            tracing::info!("Drop light sensor: {syspath:?}");
+            self.light = None;
+        }
+        if self
+            .proximity
+            .as_ref()
+            .map(|proximity| matches(&proximity.reader))
+            == Some(true)
+        {
+            tracing::info!("Drop proximity sensor: {syspath:?}");
+            self.proximity = None;
+        }
+        if self
+            .compass
+            .as_ref()
+            .map(|compass| matches(&compass.reader))
+            == Some(true)
+        {
+            tracing::info!("Drop compass: {syspath:?}");
+            self.compass = None;
+        }
+
+        self.spare_accels
+            .retain(|accel| !accel.reader.as_ref().map(matches).unwrap_or_default());
+        self.use_spare_accels();
+
+        if self.display_accel.is_none() || self.base_accel.is_none() {
+            self.hinge_angle = None;
+            self.tablet_mode = None;
+        }
+    }
+
+    /// Replace removed accelerometers by spare ones
+    fn use_spare_accels(&mut self) {
+        for location in [AccelLocation::Display, AccelLocation::Base] {
+            let slot = match location {
+                AccelLocation::Display => &mut self.display_accel,
+                AccelLocation::Base => &mut self.base_accel,
+            };
+            if slot.is_some() {
+                continue;
+            }
+            if let Some(index) = self
+                .spare_accels
+                .iter()
+                .position(|accel| accel.location == location)
+            {
+                let accel = self.spare_accels.remove(index);
+                tracing::info!("Use spare device: {accel:?}");
+                *slot = accel.into();
             }
         }
     }
 
-    /*pub fn time(&self) -> Option<&Instant> {
-        self.record.as_ref().map(|(_, time)| time)
-    }*/
+    /// Whether sensors of device already in use
+    fn has_device(&self, syspath: &Path) -> bool {
+        self.readers()
+            .chain(
+                self.spare_accels
+                    .iter()
+                    .filter_map(|accel| accel.reader.as_ref()),
+            )
+            .any(|reader| reader.device.syspath() == syspath)
+    }
 
-    pub fn value(&self) -> Option<&Vec3> {
-        self.record.as_ref().map(|(val, _)| val)
+    /// Whether display orientation can be detected
+    pub fn has_orientation(&self) -> bool {
+        self.display_accel.is_some()
     }
 
-    pub fn angular_velocity(&self) -> Option<f64> {
-        self.velocity
+    /// Whether accelerometer is present at location
+    pub fn has_accel(&self, location: AccelLocation) -> bool {
+        self.accels().any(|accel| accel.location == location)
     }
 
-    pub fn angular_acceleration(&self) -> Option<f64> {
-        self.acceleration
+    /// Whether hinge angle can be estimated
+    pub fn has_hinge(&self) -> bool {
+        self.display_accel.is_some() && self.base_accel.is_some()
     }
 
-    pub fn plane_orientation(&self) -> Option<(Orientation, f64, f64)> {
-        let value = self.value()?;
+    /// Whether sensors related to claim is present
+    pub fn has_sensor(&self, claim: Claim) -> bool {
+        match claim {
+            Claim::Orientation => self.has_orientation(),
+            Claim::Light => self.light.is_some(),
+            Claim::Proximity => self.proximity.is_some(),
+            Claim::Compass => self
+                .compass
+                .as_ref()
+                .map(|compass| !compass.needs_gravity() || self.has_orientation())
+                .unwrap_or_default(),
+            Claim::SensorData => self.accels().next().is_some(),
+        }
+    }
 
-        let z_angle = value.angle_between(Vec3::Z) - FRAC_PI_2;
+    /// Sample accelerometers in pose and return poses which still need to be sampled
+    ///
+    /// Convertible device should be opened flat (180°) in each pose, so both
+    /// base and display accelerometers see the same gravity vector, samples
+    /// are rejected otherwise. Samples which does not match pose is rejected too.
+    pub async fn calibrate(&mut self, pose: CalibrationPose) -> Result<Vec<CalibrationPose>> {
+        if self.accels().next().is_none() {
+            return Err(Error::Calibration("No accelerometers available".into()));
+        }
 
-        let xy_value = value.truncate();
+        tracing::info!("Sample accelerometers in pose: {pose}");
 
-        let xy_angle = xy_value.angle_between(Vec2::NEG_Y);
+        for accel in self.accels_mut() {
+            accel.set_active(true);
+            accel.sampling = Some((Vec3::ZERO, 0));
+        }
 
-        let orientation = if xy_angle < -FRAC_PI_4 * 3.0 {
-            Orientation::BottomUp
-        } else if xy_angle < -FRAC_PI_4 {
-            Orientation::LeftUp
-        } else if xy_angle < FRAC_PI_4 {
-            Orientation::TopUp
-        } else if xy_angle < FRAC_PI_4 * 3.0 {
-            Orientation::RightUp
-        } else {
-            Orientation::BottomUp
-        };
+        let res = self.sample_calibration().await;
 
-        let angle = match orientation {
-            Orientation::TopUp => xy_angle,
-            Orientation::LeftUp => xy_angle + FRAC_PI_2,
-            Orientation::RightUp => xy_angle - FRAC_PI_2,
-            Orientation::BottomUp => {
-                if xy_angle < 0.0 {
-                    xy_angle + PI
-                } else {
-                    xy_angle - PI
-                }
+        // stop sampling even when polling failed
+        let sums = self
+            .accels_mut()
+            .map(|accel| (accel.location, accel.mount, accel.sampling.take()))
+            .collect::<Vec<_>>();
+
+        res?;
+
+        let mut samples = Vec::new();
+        for (location, mount, sum) in sums {
+            let (sum, count) = sum.unwrap_or_default();
+            if count == 0 {
+                return Err(Error::Calibration(format!(
+                    "No {location:?} accelerometer samples"
+                )));
             }
-        };
+            let value = sum / count as f64;
+            let gravity = mount * value;
+            if !pose_matches(pose, gravity) {
+                return Err(Error::Calibration(format!(
+                    "{location:?} accelerometer measures {:.1?} which does not match pose {pose}",
+                    gravity.to_array()
+                )));
+            }
+            samples.push((location, gravity, value));
+        }
 
-        Some((orientation, z_angle, angle))
+        if let [(_, display, _), (_, base, _)] = samples[..] {
+            let disagreement = display.angle_between(base).to_degrees();
+            if disagreement > CALIBRATION_MAX_DISAGREEMENT {
+                return Err(Error::Calibration(format!(
+                    "Base and display accelerometers disagree by {disagreement:.0}°, device should be opened flat"
+                )));
+            }
+        }
+
+        for (location, _, value) in samples {
+            self.calibration_samples
+                .retain(|(had_location, had_pose, _)| {
+                    (*had_location, *had_pose) != (location, pose)
+                });
+            self.calibration_samples.push((location, pose, value));
+        }
+
+        let remaining = CalibrationPose::ALL
+            .into_iter()
+            .filter(|pose| {
+                self.accels().any(|accel| {
+                    !self
+                        .calibration_samples
+                        .iter()
+                        .any(|(location, had_pose, _)| {
+                            (*location, *had_pose) == (accel.location, *pose)
+                        })
+                })
+            })
+            .collect::<Vec<_>>();
+
+        Ok(remaining)
     }
 
-    pub fn plane_orientation_checked(&self, config: &OrientationConfig) -> Option<Orientation> {
-        let acceleration = self.angular_acceleration()?;
-        let velocity = self.angular_velocity()?;
-        let (orientation, z_angle, angle) = self.plane_orientation()?;
-        if config.check(
-            angle.into(),
-            z_angle.into(),
-            velocity.into(),
-            acceleration.into(),
-        ) {
-            Some(orientation)
-        } else {
-            None
+    /// Poll accelerometers during calibration sampling time
+    async fn sample_calibration(&mut self) -> Result<()> {
+        let deadline = Instant::now() + CALIBRATION_DURATION;
+        while Instant::now() < deadline {
+            self.wait()
+                .race(async {
+                    smol::Timer::at(deadline).await;
+                })
+                .await;
+            self.poll()?;
         }
+        Ok(())
     }
-}
 
-#[derive(Clone, Copy, Debug)]
-#[repr(u8)]
-enum SensorType {
-    Accel,
-}
+    /// Compute corrections using values sampled in all poses
+    ///
+    /// Corrections is not applied until [`Iio::set_calibration`] called,
+    /// samples is dropped when corrections cannot be computed.
+    pub fn compute_calibration(&mut self) -> Result<CalibrationConfig> {
+        let mut calibration = self.calibration.clone();
+        let locations = self
+            .accels()
+            .map(|accel| accel.location)
+            .collect::<Vec<_>>();
 
-trait DeviceExt {
-    fn sensor_type(&self) -> Option<SensorType>;
+        for location in locations {
+            let values = self
+                .calibration_samples
+                .iter()
+                .filter(|(had_location, _, _)| *had_location == location)
+                .map(|(_, _, value)| *value);
 
-    fn property_value_str(&self, property: impl AsRef<OsStr>) -> Option<&str>;
-    fn property_value_typed<T: FromStr>(&self, property: impl AsRef<OsStr>) -> Option<T>;
+            let Some(accel_calibration) = accel_calibration(values) else {
+                self.calibration_samples.clear();
+                return Err(Error::Calibration(format!(
+                    "Bad {location:?} accelerometer samples"
+                )));
+            };
 
-    fn attribute_value_str(&self, attribute: impl AsRef<OsStr>) -> Option<&str>;
-    fn attribute_value_typed<T: FromStr>(&self, attribute: impl AsRef<OsStr>) -> Option<T>;
-    fn attribute_value_uncache(&self, attribute: impl AsRef<OsStr>) -> std::io::Result<()>;
-    fn attribute_value_typed_uncached<T: FromStr>(&self, attribute: impl AsRef<OsStr>)
-        -> Option<T>;
+            tracing::info!(
+                "Calibrated {location:?} accelerometer: offset {:?} scale {:?}",
+                accel_calibration.offset,
+                accel_calibration.scale
+            );
 
-    fn accel_location(&self) -> Option<AccelLocation>;
-    fn accel_mount_matrix(&self) -> Option<Mat3>;
-    fn accel_offset(&self) -> Option<Vec3>;
-    fn accel_scale(&self) -> Option<Vec3>;
-    fn accel_raw(&self) -> Option<Vec3>;
-}
+            match location {
+                AccelLocation::Display => calibration.display = accel_calibration.into(),
+                AccelLocation::Base => calibration.base = accel_calibration.into(),
+            }
+        }
 
-impl DeviceExt for udev::Device {
-    fn sensor_type(&self) -> Option<SensorType> {
-        self.devtype()
-            .and_then(|type_| type_.to_str())
-            .filter(|type_| type_ == &"iio_device")
-            .and_then(|_| {
-                self.attribute_value("name")
-                    .and_then(|name| name.to_str())
-                    .and_then(|name| {
-                        if name.contains("accel") {
-                            Some(SensorType::Accel)
-                        } else {
-                            None
-                        }
-                    })
-            })
+        Ok(calibration)
     }
 
-    fn property_value_str(&self, property: impl AsRef<OsStr>) -> Option<&str> {
-        self.property_value(property)
-            .and_then(|value| value.to_str())
+    /// Apply accelerometers corrections and drop calibration samples
+    pub fn set_calibration(&mut self, calibration: CalibrationConfig) {
+        self.calibration_samples.clear();
+        self.calibration = calibration;
+
+        for accel in self.display_accel.iter_mut().chain(&mut self.base_accel) {
+            let calibration = match accel.location {
+                AccelLocation::Display => self.calibration.display,
+                AccelLocation::Base => self.calibration.base,
+            };
+            if let Some(calibration) = calibration {
+                accel.set_calibration(&calibration);
+            }
+        }
     }
 
-    fn attribute_value_str(&self, attribute: impl AsRef<OsStr>) -> Option<&str> {
-        self.attribute_value(attribute)
-            .and_then(|value| value.to_str())
+    /// Handle pending sensors hotplug events
+    fn handle_events(&mut self, monitor: &udev::MonitorSocket) {
+        for event in monitor.iter() {
+            match event.event_type() {
+                udev::EventType::Add | udev::EventType::Bind | udev::EventType::Change => {
+                    if !event.is_initialized() || event.sensor_types().is_empty() {
+                        continue;
+                    }
+                    if let Err(error) = self.add_device(event.device().into()) {
+                        tracing::warn!("Error while adding IIO device: {error}");
+                    }
+                }
+                udev::EventType::Remove | udev::EventType::Unbind => {
+                    self.remove_device(event.syspath());
+                }
+                _ => (),
+            }
+        }
     }
 
-    fn property_value_typed<T: FromStr>(&self, property: impl AsRef<OsStr>) -> Option<T> {
-        self.property_value_str(property)
-            .and_then(|value| value.parse().ok())
+    fn accels(&self) -> impl Iterator<Item = &Accel> {
+        self.display_accel.iter().chain(&self.base_accel)
     }
 
-    fn attribute_value_typed<T: FromStr>(&self, attribute: impl AsRef<OsStr>) -> Option<T> {
-        self.attribute_value_str(attribute)
-            .and_then(|value| value.parse().ok())
+    fn accels_mut(&mut self) -> impl Iterator<Item = &mut Accel> {
+        self.display_accel.iter_mut().chain(&mut self.base_accel)
+    }
+
+    fn readers(&self) -> impl Iterator<Item = &IioReader> {
+        self.accels()
+            .filter_map(|accel| accel.reader.as_ref())
+            .chain(self.light.iter().map(|light| &light.reader))
+            .chain(self.proximity.iter().map(|proximity| &proximity.reader))
+            .chain(self.compass.iter().map(|compass| &compass.reader))
+    }
+
+    pub fn poll(&mut self) -> Result<()> {
+        self.poll_at(util::monotonic_time())
+    }
+
+    /// Poll sensors at monotonic clock time
+    ///
+    /// The time is used for sensors which is not buffered or has no timestamps.
+    fn poll_at(&mut self, now: Duration) -> Result<()> {
+        let recorder = self.recorder.as_ref();
+        for accel in self.display_accel.iter_mut().chain(&mut self.base_accel) {
+            accel.poll(now, recorder)?;
+        }
+        if let Some(light) = &mut self.light {
+            light.poll(now)?;
+        }
+        if let Some(proximity) = &mut self.proximity {
+            proximity.poll(now)?;
+        }
+        if let Some(compass) = &mut self.compass {
+            compass.poll(now)?;
+        }
+        self.update_orientation();
+        self.update_hinge_angle();
+        Ok(())
+    }
+
+    /// Start or stop sampling sensors related to claim
+    pub fn set_active(&mut self, claim: Claim, active: bool) {
+        self.claims.retain(|had_claim| *had_claim != claim);
+        if active {
+            self.claims.push(claim);
+        }
+
+        // tilt compensation requires gravity vector
+        let gravity = self
+            .compass
+            .as_ref()
+            .map(|compass| compass.needs_gravity())
+            .unwrap_or_default();
+
+        let claimed = |claim| self.claims.contains(&claim);
+        let accel_active = claimed(Claim::Orientation)
+            || claimed(Claim::SensorData)
+            || (gravity && claimed(Claim::Compass));
+        let light_active = claimed(Claim::Light);
+        let proximity_active = claimed(Claim::Proximity);
+        let compass_active = claimed(Claim::Compass);
+
+        for accel in self.accels_mut() {
+            accel.set_active(accel_active);
+        }
+        if let Some(light) = &mut self.light {
+            light.reader.set_active(light_active);
+        }
+        if let Some(proximity) = &mut self.proximity {
+            proximity.reader.set_active(proximity_active);
+        }
+        if let Some(compass) = &mut self.compass {
+            compass.reader.set_active(compass_active);
+        }
+    }
+
+    /// Whether any sensor is sampling
+    pub fn is_active(&self) -> bool {
+        self.readers().any(IioReader::is_active)
+    }
+
+    /// Wait until new samples is available
+    pub async fn wait(&self) {
+        let polling = self.readers().any(IioReader::is_polling);
+
+        let timer = async move {
+            if polling {
+                smol::Timer::after(POLL_INTERVAL).await;
+            } else {
+                smol::future::pending::<()>().await;
+            }
+        }
+        .boxed_local();
+
+        self.readers()
+            .filter_map(IioReader::buffer)
+            .fold(timer, |wait, buffer| {
+                wait.race(async move {
+                    if let Err(error) = buffer.readable().await {
+                        tracing::warn!("Error while waiting IIO buffer: {error}");
+                    }
+                })
+                .boxed_local()
+            })
+            .await
+    }
+
+    fn update_hinge_angle(&mut self) {
+        let Some(angle) = self
+            .base_accel
+            .as_ref()
+            .and_then(|accel| accel.value())
+            .and_then(|base| {
+                self.display_accel
+                    .as_ref()
+                    .and_then(|accel| accel.value())
+                    .and_then(|display| {
+                        hinge_angle(base, display, self.tablet_mode_config.min_hinge_tilt)
+                    })
+            })
+        else {
+            return;
+        };
+
+        // Gravity alone cannot distinguish closed lid from fully folded one,
+        // so keep the angle on the side of the seam it came from.
+        let angle = match self.hinge_angle {
+            Some(last) if last > PI + FRAC_PI_2 && angle < FRAC_PI_2 => TAU,
+            Some(last) if last < FRAC_PI_2 && angle > PI + FRAC_PI_2 => 0.0,
+            _ => angle,
+        };
+
+        self.hinge_angle = Some(angle);
+        self.tablet_mode = Some(self.tablet_mode_config.check(angle, self.tablet_mode));
+    }
+
+    fn update_orientation(&mut self) {
+        let Some(accel) = &self.display_accel else {
+            return;
+        };
+        let config = &self.orientation_config;
+
+        // keep current orientation until device turned past boundary by margin
+        if let Some(current) = self.orientation {
+            if accel
+                .xy_deviation(current)
+                .map(|angle| angle.abs() < FRAC_PI_4 + config.hysteresis)
+                .unwrap_or(true)
+            {
+                self.pending_orientation = None;
+                return;
+            }
+        }
+
+        let Some(orientation) = accel.plane_orientation_checked(config) else {
+            self.pending_orientation = None;
+            return;
+        };
+
+        let now = accel.time().copied().unwrap_or_default();
+        let since = match self.pending_orientation {
+            Some((pending, since)) if pending == orientation => since,
+            _ => {
+                self.pending_orientation = Some((orientation, now));
+                now
+            }
+        };
+
+        if self.orientation.is_none()
+            || now.saturating_sub(since) >= Duration::from_secs_f64(config.stable_time)
+        {
+            self.orientation = Some(orientation);
+            self.pending_orientation = None;
+        }
+    }
+
+    pub fn display_orientation(&self) -> Option<Orientation> {
+        self.orientation
+    }
+
+    /// Whether display lies flat and whether it faces up
+    ///
+    /// Device is flat when orientation cannot be detected due to Z angle.
+    pub fn display_flat(&self) -> Option<(bool, bool)> {
+        let (_, z_angle, _) = self.display_accel.as_ref()?.plane_orientation()?;
+
+        Some((
+            z_angle.abs() > self.orientation_config.max_z_angle,
+            z_angle > 0.0,
+        ))
+    }
+
+    /// Hinge angle in radians from 0 (closed) to 2π (fully folded)
+    pub fn hinge_angle(&self) -> Option<f64> {
+        self.hinge_angle
+    }
+
+    pub fn tablet_mode(&self) -> Option<bool> {
+        self.tablet_mode
+    }
+
+    /// Ambient light level in lux
+    pub fn light_level(&self) -> Option<f64> {
+        self.light.as_ref().and_then(|light| light.value())
+    }
+
+    /// Whether object is near to proximity sensor
+    pub fn proximity_near(&self) -> Option<bool> {
+        self.proximity
+            .as_ref()
+            .and_then(|proximity| proximity.value())
+    }
+
+    /// Display accelerometer measurements
+    pub fn display_accel_data(&self) -> Option<AccelData> {
+        self.display_accel.as_ref().and_then(Accel::data)
+    }
+
+    /// Base accelerometer measurements
+    pub fn base_accel_data(&self) -> Option<AccelData> {
+        self.base_accel.as_ref().and_then(Accel::data)
+    }
+
+    /// Compass heading in degrees from 0 to 360
+    pub fn compass_heading(&self) -> Option<f64> {
+        match self.compass.as_ref()?.value()? {
+            CompassValue::Heading(heading) => Some(heading),
+            CompassValue::Field(field) => self
+                .display_accel
+                .as_ref()
+                .and_then(|accel| accel.value())
+                .and_then(|gravity| tilt_compensated_heading(gravity, &field)),
+        }
+    }
+
+    pub fn posture(&self) -> Option<Posture> {
+        let angle = self.hinge_angle?;
+        let config = &self.tablet_mode_config;
+
+        Some(if angle < config.closed_angle {
+            Posture::Closed
+        } else if !self.tablet_mode? {
+            Posture::Laptop
+        } else if angle > config.folded_angle {
+            Posture::Tablet
+        } else if self
+            .base_accel
+            .as_ref()
+            .and_then(|accel| accel.value())
+            .map(|base| base.angle_between(Vec3::Z) < FRAC_PI_4)
+            .unwrap_or_default()
+        {
+            // keyboard faces down to the table
+            Posture::Stand
+        } else {
+            Posture::Tent
+        })
+    }
+
+    pub async fn process(
+        devices: Vec<PathBuf>,
+        service: Service,
+        config: &Config,
+    ) -> Result<Option<async_signal::Signal>> {
+        // start monitoring before enumeration to not miss devices
+        // (emulated sensors is not hotplugged)
+        let monitor = if config.iio.sysfs_root.is_none() {
+            Some(Async::new(
+                udev::MonitorBuilder::new()?
+                    .match_subsystem("iio")?
+                    .listen()?,
+            )?)
+        } else {
+            None
+        };
+        let mut iio = Self::from_paths(devices, config)?;
+        iio.set_recorder(service.recorder().await);
+        // continue from published state when restarted on reload
+        let mut detections = Detections::from(service.sensor_state().await);
+        let mut calibration_request = None;
+
+        loop {
+            if let Some(monitor) = &monitor {
+                iio.handle_events(monitor.get_ref());
+            }
+
+            if let Some(CalibrationRequest { pose, reply }) = calibration_request.take() {
+                // storage checked before sampling to not fail after all poses
+                let res = match config.check_calibration_path().await {
+                    Ok(()) => iio.calibrate(pose).await,
+                    Err(error) => Err(error),
+                };
+                let res = match res {
+                    // corrections applied only when stored to not lose it on restart
+                    Ok(remaining) if remaining.is_empty() => match iio.compute_calibration() {
+                        Ok(calibration) => config.save_calibration(&calibration).await.map(|_| {
+                            iio.set_calibration(calibration);
+                            remaining
+                        }),
+                        Err(error) => Err(error),
+                    },
+                    res => res,
+                };
+                let _ = reply.try_send(res);
+            }
+
+            for detection in detections.removed(&iio) {
+                detection.publish(&service).await;
+            }
+
+            let claimed = service.claimed_sensors().await;
+
+            // measurements is frozen when sensor removed or released
+            let sensor_data = claimed.contains(&Claim::SensorData);
+            if !sensor_data || !iio.has_accel(AccelLocation::Display) {
+                if let Err(error) = service.unset_display_accel().await {
+                    tracing::warn!("Error while unsetting display accel data: {error}");
+                }
+            }
+            if !sensor_data || !iio.has_accel(AccelLocation::Base) {
+                if let Err(error) = service.unset_base_accel().await {
+                    tracing::warn!("Error while unsetting base accel data: {error}");
+                }
+            }
+
+            for claim in Claim::ALL {
+                if let Err(error) = service
+                    .set_sensor_available(claim, iio.has_sensor(claim))
+                    .await
+                {
+                    tracing::warn!("Error while setting sensor availability: {error}");
+                }
+                iio.set_active(claim, claimed.contains(&claim));
+            }
+
+            let hotplug = async {
+                let Some(monitor) = &monitor else {
+                    return smol::future::pending().await;
+                };
+                if let Err(error) = monitor.readable().await {
+                    tracing::warn!("Error while waiting udev monitor: {error}");
+                }
+            };
+
+            let calibration = async {
+                calibration_request = service.wait_calibration().await;
+            };
+
+            if !iio.is_active() {
+                tracing::debug!("Sensors polling paused");
+                service
+                    .wait_claims(&claimed)
+                    .race(hotplug)
+                    .race(calibration)
+                    .await;
+                continue;
+            }
+
+            if let Err(error) = iio.poll() {
+                tracing::warn!("Error while polling IIO sensors: {error}");
+            }
+
+            let lid_closed = service.is_lid_closed().await;
+
+            for detection in detections.update(&iio, lid_closed) {
+                detection.publish(&service).await;
+            }
+
+            if claimed.contains(&Claim::SensorData) {
+                if let Some(data) = iio.display_accel_data() {
+                    if let Err(error) = service.set_display_accel(data).await {
+                        tracing::warn!("Error while setting display accel data: {error}");
+                    }
+                }
+                if let Some(data) = iio.base_accel_data() {
+                    if let Err(error) = service.set_base_accel(data).await {
+                        tracing::warn!("Error while setting base accel data: {error}");
+                    }
+                }
+            }
+
+            iio.wait()
+                .race(service.wait_claims(&claimed))
+                .race(hotplug)
+                .race(calibration)
+                .await;
+        }
+    }
+}
+
+/// Detected change of sensors state
+///
+/// Missing value means that sensor is removed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Detection {
+    Orientation(Option<Orientation>),
+    /// Flat and facing up
+    Flat(Option<(bool, bool)>),
+    /// Hinge angle in degrees
+    HingeAngle(Option<f64>),
+    Posture(Option<Posture>),
+    /// Tablet mode estimated using hinge angle
+    TabletMode(Option<bool>),
+    LightLevel(Option<f64>),
+    ProximityNear(Option<bool>),
+    CompassHeading(Option<f64>),
+}
+
+impl Detection {
+    /// Trace event of detected decision
+    pub fn trace_event(&self) -> Option<TraceEvent> {
+        Some(match *self {
+            Self::Orientation(orient) => TraceEvent::Orientation(orient?),
+            Self::Flat(flat) => {
+                let (flat, facing_up) = flat?;
+                TraceEvent::Flat { flat, facing_up }
+            }
+            Self::Posture(posture) => TraceEvent::Posture(posture?),
+            Self::TabletMode(mode) => TraceEvent::TabletMode(mode?),
+            _ => return None,
+        })
+    }
+
+    /// Publish change to service state
+    pub async fn publish(self, service: &Service) {
+        if let Some(event) = self.trace_event() {
+            service.record(event).await;
+        }
+
+        let res = match self {
+            Self::Orientation(Some(orient)) => {
+                tracing::debug!("Detected orientation change: {orient:?}");
+                service.set_orientation(orient).await
+            }
+            Self::Orientation(None) => {
+                tracing::debug!("Display accelerometer removed");
+                service.unset_orientation().await
+            }
+            Self::Flat(Some((flat, facing_up))) => {
+                tracing::debug!("Detected flat state change: {:?}", (flat, facing_up));
+                service.set_flat(flat, facing_up).await
+            }
+            Self::Flat(None) => service.unset_flat().await,
+            Self::HingeAngle(Some(angle)) => {
+                tracing::trace!("Hinge angle: {angle:.1}");
+                service.set_hinge_angle(angle).await
+            }
+            Self::HingeAngle(None) => {
+                tracing::debug!("Base accelerometer removed");
+                service.unset_hinge_angle().await
+            }
+            Self::Posture(Some(posture)) => {
+                tracing::debug!("Detected posture change: {posture:?}");
+                service.set_posture(posture).await
+            }
+            Self::Posture(None) => service.unset_posture().await,
+            Self::TabletMode(Some(mode)) => {
+                tracing::debug!("Detected tablet-mode change: {mode:?}");
+                service.set_tablet_mode(TabletModeSource::Hinge, mode).await
+            }
+            // input devices keeps reporting mode when present
+            Self::TabletMode(None) => service.unset_tablet_mode(TabletModeSource::Hinge).await,
+            Self::LightLevel(Some(level)) => {
+                tracing::debug!("Detected light level change: {level}");
+                service.set_light_level(level).await
+            }
+            Self::LightLevel(None) => {
+                tracing::debug!("Light sensor removed");
+                service.unset_light_level().await
+            }
+            Self::ProximityNear(Some(near)) => {
+                tracing::debug!("Detected proximity change: {near:?}");
+                service.set_proximity_near(near).await
+            }
+            Self::ProximityNear(None) => {
+                tracing::debug!("Proximity sensor removed");
+                service.unset_proximity_near().await
+            }
+            Self::CompassHeading(Some(heading)) => {
+                tracing::trace!("Detected compass heading change: {heading:.1}");
+                service.set_compass_heading(heading).await
+            }
+            Self::CompassHeading(None) => {
+                tracing::debug!("Compass removed");
+                service.unset_compass_heading().await
+            }
+        };
+
+        if let Err(error) = res {
+            tracing::warn!("Error while publishing {self:?}: {error}");
+        }
+    }
+}
+
+/// Detection results reported last
+#[derive(Default)]
+pub struct Detections {
+    orientation: Option<Orientation>,
+    flat: Option<(bool, bool)>,
+    hinge_angle: Option<f64>,
+    posture: Option<Posture>,
+    tablet_mode: Option<bool>,
+    light_level: Option<f64>,
+    proximity_near: Option<bool>,
+    compass_heading: Option<f64>,
+}
+
+impl From<SensorState> for Detections {
+    fn from(state: SensorState) -> Self {
+        Self {
+            orientation: state.orientation,
+            flat: state.flat,
+            hinge_angle: state.hinge_angle,
+            posture: state.posture,
+            tablet_mode: state.tablet_mode,
+            light_level: state.light_level,
+            proximity_near: state.proximity_near,
+            compass_heading: state.compass_heading,
+        }
+    }
+}
+
+impl Detections {
+    /// Get detection results which sensors is removed since last update
+    pub fn removed(&mut self, iio: &Iio) -> Vec<Detection> {
+        let mut detections = Vec::new();
+
+        if !iio.has_orientation() {
+            if self.orientation.take().is_some() {
+                detections.push(Detection::Orientation(None));
+            }
+            if self.flat.take().is_some() {
+                detections.push(Detection::Flat(None));
+            }
+        }
+
+        if !iio.has_hinge() {
+            if self.hinge_angle.take().is_some() {
+                detections.push(Detection::HingeAngle(None));
+            }
+            if self.posture.take().is_some() {
+                detections.push(Detection::Posture(None));
+            }
+            if self.tablet_mode.take().is_some() {
+                detections.push(Detection::TabletMode(None));
+            }
+        }
+
+        if !iio.has_sensor(Claim::Light) && self.light_level.take().is_some() {
+            detections.push(Detection::LightLevel(None));
+        }
+
+        if !iio.has_sensor(Claim::Proximity) && self.proximity_near.take().is_some() {
+            detections.push(Detection::ProximityNear(None));
+        }
+
+        if !iio.has_sensor(Claim::Compass) && self.compass_heading.take().is_some() {
+            detections.push(Detection::CompassHeading(None));
+        }
+
+        detections
+    }
+
+    /// Get detection results changed since last update
+    pub fn update(&mut self, iio: &Iio, lid_closed: bool) -> Vec<Detection> {
+        let mut detections = Vec::new();
+
+        // closed lid makes orientation meaningless
+        if let Some(orient) = iio.display_orientation().filter(|_| !lid_closed) {
+            if self.orientation != Some(orient) {
+                self.orientation = orient.into();
+                detections.push(Detection::Orientation(orient.into()));
+            }
+        }
+
+        if let Some(flat) = iio.display_flat() {
+            if self.flat != Some(flat) {
+                self.flat = flat.into();
+                detections.push(Detection::Flat(flat.into()));
+            }
+        }
+
+        if let Some(angle) = iio.hinge_angle().map(f64::to_degrees) {
+            if !self
+                .hinge_angle
+                .map(|last_angle| (last_angle - angle).abs() < HINGE_ANGLE_STEP)
+                .unwrap_or_default()
+            {
+                self.hinge_angle = angle.into();
+                detections.push(Detection::HingeAngle(angle.into()));
+            }
+        }
+
+        if let Some(posture) = iio.posture() {
+            if self.posture != Some(posture) {
+                self.posture = posture.into();
+                detections.push(Detection::Posture(posture.into()));
+            }
+        }
+
+        if let Some(mode) = iio.tablet_mode() {
+            if self.tablet_mode != Some(mode) {
+                self.tablet_mode = mode.into();
+                detections.push(Detection::TabletMode(mode.into()));
+            }
+        }
+
+        if let Some(level) = iio.light_level() {
+            if !self
+                .light_level
+                .map(|last_level| {
+                    (last_level - level).abs()
+                        < (last_level * LIGHT_LEVEL_RATIO).max(LIGHT_LEVEL_STEP)
+                })
+                .unwrap_or_default()
+            {
+                self.light_level = level.into();
+                detections.push(Detection::LightLevel(level.into()));
+            }
+        }
+
+        if let Some(near) = iio.proximity_near() {
+            if self.proximity_near != Some(near) {
+                self.proximity_near = near.into();
+                detections.push(Detection::ProximityNear(near.into()));
+            }
+        }
+
+        if let Some(heading) = iio.compass_heading() {
+            if !self
+                .compass_heading
+                .map(|last_heading| {
+                    let delta = (last_heading - heading).abs();
+                    delta.min(360.0 - delta) < HEADING_STEP
+                })
+                .unwrap_or_default()
+            {
+                self.compass_heading = heading.into();
+                detections.push(Detection::CompassHeading(heading.into()));
+            }
+        }
+
+        detections
+    }
+}
+
+#[derive(Debug)]
+struct Accel {
+    /// Samples reader, missing for replayed sensor
+    reader: Option<IioReader>,
+    /// Sensor location
+    location: AccelLocation,
+    /// Sensor mount matrix
+    mount: Mat3,
+    /// Sensor data offset
+    offset: Vec3,
+    /// Sensor data scale
+    scale: Vec3,
+    /// Calibration offset
+    calibration_offset: Vec3,
+    /// Calibration scale
+    calibration_scale: Vec3,
+    /// Sum and number of values sampled for calibration
+    sampling: Option<(Vec3, usize)>,
+    /// Samples filter
+    filter: Filter,
+    /// Latest raw sample
+    raw: Option<Vec3>,
+    /// Whether parameters is written to trace
+    recorded: bool,
+    /// Latest data with time
+    record: Option<(Vec3, Duration)>,
+    /// Data with time and angular velocity over derivatives window
+    window: VecDeque<(Vec3, Duration, Option<f64>)>,
+    /// Angular velocity, rad/sec
+    velocity: Option<f64>,
+    /// Angular acceleration, rad/sec^2
+    acceleration: Option<f64>,
+}
+
+impl Accel {
+    pub fn new(
+        device: IioDevice,
+        buffered: bool,
+        config: &IioConfig,
+        sensor: Option<&SensorConfig>,
+    ) -> Result<Self> {
+        let location = sensor
+            .and_then(|sensor| sensor.location)
+            .or_else(|| device.accel_location())
+            .unwrap_or_default();
+        let mount = sensor
+            .and_then(SensorConfig::mount_matrix)
+            .or_else(|| device.accel_mount_matrix())
+            .unwrap_or(Mat3::IDENTITY);
+        let offset = sensor
+            .and_then(|sensor| sensor.offset)
+            .map(Vec3::from_array)
+            .or_else(|| device.accel_offset())
+            .unwrap_or(Vec3::ZERO);
+        let scale = sensor
+            .and_then(|sensor| sensor.scale)
+            .map(Vec3::from_array)
+            .or_else(|| device.accel_scale())
+            .unwrap_or(Vec3::ONE);
+        let reader = IioReader::new(device, ACCEL_CHANNELS, "_raw", buffered, config);
+
+        Ok(Self {
+            reader: reader.into(),
+            location,
+            mount,
+            offset,
+            scale,
+            calibration_offset: Vec3::ZERO,
+            calibration_scale: Vec3::ONE,
+            sampling: None,
+            filter: Filter::default(),
+            raw: None,
+            recorded: false,
+            record: Default::default(),
+            window: Default::default(),
+            velocity: Default::default(),
+            acceleration: Default::default(),
+        })
+    }
+
+    /// Create sensor which samples is pushed from trace
+    pub fn replay(location: AccelLocation, filter: Filter) -> Self {
+        Self {
+            reader: None,
+            location,
+            mount: Mat3::IDENTITY,
+            offset: Vec3::ZERO,
+            scale: Vec3::ONE,
+            calibration_offset: Vec3::ZERO,
+            calibration_scale: Vec3::ONE,
+            sampling: None,
+            filter,
+            raw: None,
+            recorded: false,
+            record: Default::default(),
+            window: Default::default(),
+            velocity: Default::default(),
+            acceleration: Default::default(),
+        }
+    }
+
+    pub fn poll(&mut self, now: Duration, recorder: Option<&Recorder>) -> Result<()> {
+        let Some(reader) = &mut self.reader else {
+            return Ok(());
+        };
+        let mut samples = Vec::new();
+        reader.read(now, |values, time| {
+            samples.push((vec3(values[0], values[1], values[2]), time));
+        })?;
+        for (raw, time) in samples {
+            if let Some(recorder) = recorder {
+                if let Err(error) = self.record(recorder, raw, time) {
+                    tracing::warn!("Error while recording trace: {error}");
+                }
+            }
+            self.push_raw(raw, time);
+        }
+        Ok(())
+    }
+
+    /// Write raw sample to trace preceded by sensor parameters
+    fn record(&mut self, recorder: &Recorder, raw: Vec3, time: Duration) -> Result<()> {
+        if !self.recorded {
+            recorder.record(&TraceEvent::Sensor {
+                location: self.location,
+                mount: self.mount.to_cols_array_2d(),
+                offset: self.offset.to_array(),
+                scale: self.scale.to_array(),
+            })?;
+            self.recorded = true;
+        }
+        recorder.record(&TraceEvent::Accel {
+            location: self.location,
+            time,
+            raw: raw.to_array(),
+        })
+    }
+
+    /// Convert raw sample and push it to filter
+    fn push_raw(&mut self, raw: Vec3, time: Duration) {
+        self.raw = Some(raw);
+        let value = self.correct(raw);
+        self.push(value, time);
+    }
+
+    pub fn set_active(&mut self, active: bool) {
+        if !active {
+            // drop outdated data to avoid wrong velocity estimation
+            self.record = None;
+            self.window.clear();
+            self.velocity = None;
+            self.acceleration = None;
+            self.filter.reset();
+        }
+        if let Some(reader) = &mut self.reader {
+            reader.set_active(active);
+        }
+    }
+
+    pub fn set_filter(&mut self, filter: Filter) {
+        self.filter = filter;
+    }
+
+    pub fn set_calibration(&mut self, calibration: &AccelCalibration) {
+        self.calibration_offset = Vec3::from_array(calibration.offset);
+        self.calibration_scale = Vec3::from_array(calibration.scale);
+    }
+
+    /// Convert raw sample to device frame
+    fn correct(&mut self, raw: Vec3) -> Vec3 {
+        let val = processed(raw, self.offset, self.scale);
+        if let Some((sum, count)) = &mut self.sampling {
+            *sum += val;
+            *count += 1;
+        }
+        let val = (val - self.calibration_offset) * self.calibration_scale;
+        self.mount * val
+    }
+
+    fn push(&mut self, value: Vec3, time: Duration) {
+        let delta_time = self
+            .record
+            .map(|(_, had_time)| time.saturating_sub(had_time).as_secs_f64())
+            .unwrap_or_default();
+        let value = self.filter.apply(value, delta_time);
+        self.record = Some((value, time));
+
+        // oldest sample within window is used, so each sample gets current estimation
+        while self.window.len() > 1
+            && self
+                .window
+                .front()
+                .map(|(_, had_time, _)| time.saturating_sub(*had_time) > DERIVATIVE_WINDOW)
+                .unwrap_or_default()
+        {
+            self.window.pop_front();
+        }
+
+        let mut velocity = None;
+        if let Some((had_value, had_time, had_velocity)) = self.window.front() {
+            let window_time = time.saturating_sub(*had_time).as_secs_f64();
+            if window_time > 0.0 {
+                let new_velocity = value.angle_between(*had_value) / window_time;
+                if let Some(had_velocity) = had_velocity {
+                    let acceleration = (new_velocity - had_velocity) / window_time;
+                    self.acceleration.replace(acceleration);
+                }
+                self.velocity.replace(new_velocity);
+                velocity = Some(new_velocity);
+            }
+        }
+        self.window.push_back((value, time, velocity));
+    }
+
+    pub fn time(&self) -> Option<&Duration> {
+        self.record.as_ref().map(|(_, time)| time)
+    }
+
+    pub fn value(&self) -> Option<&Vec3> {
+        self.record.as_ref().map(|(val, _)| val)
+    }
+
+    pub fn angular_velocity(&self) -> Option<f64> {
+        self.velocity
+    }
+
+    pub fn angular_acceleration(&self) -> Option<f64> {
+        self.acceleration
+    }
+
+    pub fn plane_orientation(&self) -> Option<(Orientation, f64, f64)> {
+        let value = self.value()?;
+
+        let z_angle = value.angle_between(Vec3::Z) - FRAC_PI_2;
+
+        let xy_value = value.truncate();
+
+        let xy_angle = xy_value.angle_between(Vec2::NEG_Y);
+
+        let orientation = if xy_angle < -FRAC_PI_4 * 3.0 {
+            Orientation::BottomUp
+        } else if xy_angle < -FRAC_PI_4 {
+            Orientation::LeftUp
+        } else if xy_angle < FRAC_PI_4 {
+            Orientation::TopUp
+        } else if xy_angle < FRAC_PI_4 * 3.0 {
+            Orientation::RightUp
+        } else {
+            Orientation::BottomUp
+        };
+
+        let angle = match orientation {
+            Orientation::TopUp => xy_angle,
+            Orientation::LeftUp => xy_angle + FRAC_PI_2,
+            Orientation::RightUp => xy_angle - FRAC_PI_2,
+            Orientation::BottomUp => {
+                if xy_angle < 0.0 {
+                    xy_angle + PI
+                } else {
+                    xy_angle - PI
+                }
+            }
+        };
+
+        Some((orientation, z_angle, angle))
+    }
+
+    /// Signed angle in XY plane between gravity and orientation down direction
+    pub fn xy_deviation(&self, orientation: Orientation) -> Option<f64> {
+        let xy_angle = self.value()?.truncate().angle_between(Vec2::NEG_Y);
+
+        let center = match orientation {
+            Orientation::TopUp => 0.0,
+            Orientation::LeftUp => -FRAC_PI_2,
+            Orientation::RightUp => FRAC_PI_2,
+            Orientation::BottomUp => PI,
+        };
+
+        Some((xy_angle - center + PI).rem_euclid(TAU) - PI)
+    }
+
+    /// Measurements with angles in degrees
+    pub fn data(&self) -> Option<AccelData> {
+        let value = self.value()?;
+        let (_, z_angle, xy_angle) = self.plane_orientation()?;
+
+        Some(AccelData {
+            x: value.x,
+            y: value.y,
+            z: value.z,
+            xy_angle: xy_angle.to_degrees(),
+            z_angle: z_angle.to_degrees(),
+            velocity: self.angular_velocity().unwrap_or_default().to_degrees(),
+            acceleration: self.angular_acceleration().unwrap_or_default().to_degrees(),
+        })
+    }
+
+    pub fn plane_orientation_checked(&self, config: &OrientationConfig) -> Option<Orientation> {
+        let acceleration = self.angular_acceleration()?;
+        let velocity = self.angular_velocity()?;
+        let (orientation, z_angle, angle) = self.plane_orientation()?;
+        if config.check(
+            angle.into(),
+            z_angle.into(),
+            velocity.into(),
+            acceleration.into(),
+        ) {
+            Some(orientation)
+        } else {
+            None
+        }
+    }
+}
+
+/// Accelerometer samples filter
+#[derive(Clone, Debug, Default)]
+enum Filter {
+    /// Use samples as is
+    #[default]
+    None,
+    /// Exponential moving average
+    Ema {
+        /// Time constant, sec
+        time: f64,
+        /// Filtered value
+        value: Option<Vec3>,
+    },
+    /// Per-axis Kalman filter with constant value model
+    Kalman {
+        /// Process noise variance per second
+        process_noise: f64,
+        /// Measurement noise variance
+        measurement_noise: f64,
+        /// Estimated value and its error variance
+        state: Option<(Vec3, Vec3)>,
+    },
+}
+
+impl Filter {
+    pub fn new(config: &OrientationConfig) -> Self {
+        match config.filter {
+            OrientationFilter::None => Self::None,
+            OrientationFilter::Ema => Self::Ema {
+                time: config.filter_time,
+                value: None,
+            },
+            OrientationFilter::Kalman => Self::Kalman {
+                process_noise: config.process_noise,
+                measurement_noise: config.measurement_noise,
+                state: None,
+            },
+        }
+    }
+
+    /// Filter sample using time in seconds elapsed since previous one
+    pub fn apply(&mut self, sample: Vec3, delta_time: f64) -> Vec3 {
+        match self {
+            Self::None => sample,
+            Self::Ema { time, value } => {
+                let value = value.get_or_insert(sample);
+                if *time > 0.0 {
+                    let alpha = 1.0 - (-delta_time / *time).exp();
+                    *value += (sample - *value) * alpha;
+                } else {
+                    *value = sample;
+                }
+                *value
+            }
+            Self::Kalman {
+                process_noise,
+                measurement_noise,
+                state,
+            } => {
+                let (value, error) = state.get_or_insert((sample, Vec3::splat(*measurement_noise)));
+                // predict
+                *error += Vec3::splat(*process_noise * delta_time);
+                // update
+                let gain = *error / (*error + Vec3::splat(*measurement_noise));
+                *value += (sample - *value) * gain;
+                *error *= Vec3::ONE - gain;
+                *value
+            }
+        }
+    }
+
+    pub fn reset(&mut self) {
+        match self {
+            Self::None => (),
+            Self::Ema { value, .. } => *value = None,
+            Self::Kalman { state, .. } => *state = None,
+        }
+    }
+}
+
+/// Ambient light sensor
+#[derive(Debug)]
+struct Light {
+    /// Samples reader
+    reader: IioReader,
+    /// Sensor data offset
+    offset: f64,
+    /// Sensor data scale
+    scale: f64,
+    /// Latest illuminance, lux
+    value: Option<f64>,
+}
+
+impl Light {
+    pub fn new(device: IioDevice, buffered: bool, config: &IioConfig) -> Result<Self> {
+        let channel = device
+            .light_channel()
+            .ok_or_else(|| IioError::Poll("illuminance".into()))?;
+        // processed value is already in lux
+        let raw = device
+            .attribute_value_str(format!("{channel}_raw"))
+            .is_some();
+        let (offset, scale) = if raw {
+            (
+                device
+                    .attribute_value_typed(format!("{channel}_offset"))
+                    .unwrap_or(0.0),
+                device
+                    .attribute_value_typed(format!("{channel}_scale"))
+                    .unwrap_or(1.0),
+            )
+        } else {
+            (0.0, 1.0)
+        };
+        let suffix = if raw { "_raw" } else { "_input" };
+        let reader = IioReader::new(device, &[channel], suffix, buffered && raw, config);
+
+        Ok(Self {
+            reader,
+            offset,
+            scale,
+            value: None,
+        })
+    }
+
+    pub fn poll(&mut self, now: Duration) -> Result<()> {
+        let mut raw = None;
+        self.reader.read(now, |values, _| raw = Some(values[0]))?;
+        if let Some(raw) = raw {
+            self.value = Some(processed(raw, self.offset, self.scale));
+        }
+        Ok(())
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        self.value
+    }
+}
+
+/// Proximity sensor
+#[derive(Debug)]
+struct Proximity {
+    /// Samples reader
+    reader: IioReader,
+    /// Raw value above which object is near
+    near_level: f64,
+    /// Whether object is near
+    value: Option<bool>,
+}
+
+impl Proximity {
+    pub fn new(device: IioDevice, buffered: bool, config: &IioConfig) -> Result<Self> {
+        let channel = device
+            .proximity_channel()
+            .ok_or_else(|| IioError::Poll("proximity".into()))?;
+        let near_level = device
+            .property_value_typed("PROXIMITY_NEAR_LEVEL")
+            .or_else(|| device.attribute_value_typed(format!("{channel}_nearlevel")))
+            .ok_or_else(|| IioError::Poll("proximity near level".into()))?;
+        let reader = IioReader::new(device, &[channel], "_raw", buffered, config);
+
+        Ok(Self {
+            reader,
+            near_level,
+            value: None,
+        })
+    }
+
+    pub fn poll(&mut self, now: Duration) -> Result<()> {
+        let mut raw = None;
+        self.reader.read(now, |values, _| raw = Some(values[0]))?;
+        if let Some(raw) = raw {
+            self.value = Some(raw > self.near_level);
+        }
+        Ok(())
+    }
+
+    pub fn value(&self) -> Option<bool> {
+        self.value
+    }
+}
+
+/// Compass sensor value
+#[derive(Clone, Copy, Debug)]
+enum CompassValue {
+    /// Tilt compensated heading in degrees
+    Heading(f64),
+    /// Magnetic field vector
+    Field(Vec3),
+}
+
+/// Magnetometer or compass sensor
+#[derive(Debug)]
+struct Compass {
+    /// Samples reader
+    reader: IioReader,
+    /// Whether sensor reports heading instead of magnetic field
+    heading: bool,
+    /// Sensor mount matrix
+    mount: Mat3,
+    /// Sensor data offset
+    offset: Vec3,
+    /// Sensor data scale
+    scale: Vec3,
+    /// Latest value
+    value: Option<CompassValue>,
+}
+
+impl Compass {
+    pub fn new(device: IioDevice, buffered: bool, config: &IioConfig) -> Result<Self> {
+        let heading = device
+            .attribute_value_str(format!("{HEADING_CHANNEL}_raw"))
+            .is_some();
+
+        let (mount, offset, scale) = if heading {
+            let offset = device
+                .attribute_value_typed(format!("{HEADING_CHANNEL}_offset"))
+                .unwrap_or(0.0);
+            let scale = device
+                .attribute_value_typed(format!("{HEADING_CHANNEL}_scale"))
+                .unwrap_or(1.0);
+            (Mat3::IDENTITY, Vec3::splat(offset), Vec3::splat(scale))
+        } else {
+            (
+                device.magn_mount_matrix().unwrap_or(Mat3::IDENTITY),
+                device.magn_offset().unwrap_or(Vec3::ZERO),
+                device.magn_scale().unwrap_or(Vec3::ONE),
+            )
+        };
+
+        let reader = if heading {
+            IioReader::new(device, &[HEADING_CHANNEL], "_raw", buffered, config)
+        } else {
+            IioReader::new(device, MAGN_CHANNELS, "_raw", buffered, config)
+        };
+
+        Ok(Self {
+            reader,
+            heading,
+            mount,
+            offset,
+            scale,
+            value: None,
+        })
+    }
+
+    pub fn poll(&mut self, now: Duration) -> Result<()> {
+        let mut raw = None;
+        self.reader
+            .read(now, |values, _| raw = Some(values.to_vec()))?;
+        if let Some(raw) = raw {
+            self.value = Some(if self.heading {
+                CompassValue::Heading(
+                    processed(raw[0], self.offset.x, self.scale.x).rem_euclid(360.0),
+                )
+            } else {
+                let raw = vec3(raw[0], raw[1], raw[2]);
+                CompassValue::Field(self.mount * processed(raw, self.offset, self.scale))
+            });
+        }
+        Ok(())
+    }
+
+    /// Whether gravity vector required to compute heading
+    pub fn needs_gravity(&self) -> bool {
+        !self.heading
+    }
+
+    pub fn value(&self) -> Option<CompassValue> {
+        self.value
+    }
+}
+
+/// Sensor samples reader
+///
+/// Uses triggered buffer when supported or polls sysfs attributes otherwise.
+#[derive(Debug)]
+struct IioReader {
+    /// Associated device
+    device: IioDevice,
+    /// Channel attributes to poll
+    attributes: Vec<String>,
+    /// Triggered buffer when supported
+    buffer: Option<IioBuffer>,
+    /// Latest poll time
+    polled: Option<Duration>,
+    /// Whether sampling is active
+    active: bool,
+}
+
+impl IioReader {
+    /// Create reader for channels
+    ///
+    /// The attributes `<channel><suffix>` is polled when buffer is not used.
+    pub fn new(
+        device: IioDevice,
+        channels: &[&str],
+        suffix: &str,
+        buffered: bool,
+        config: &IioConfig,
+    ) -> Self {
+        let buffer = match &device {
+            IioDevice::Udev(device) if buffered && config.buffer => {
+                IioBuffer::new(device, channels, config.sampling_frequency)
+            }
+            _ => None,
+        };
+        let attributes = channels
+            .iter()
+            .map(|channel| format!("{channel}{suffix}"))
+            .collect();
+
+        Self {
+            device,
+            attributes,
+            buffer,
+            polled: None,
+            active: true,
+        }
+    }
+
+    /// Read available samples
+    ///
+    /// The channel values and monotonic clock time passed to function for each sample,
+    /// current time is used when buffer is not used or has no timestamps.
+    pub fn read(&mut self, now: Duration, mut func: impl FnMut(&[f64], Duration)) -> Result<()> {
+        if !self.active {
+            return Ok(());
+        }
+
+        if let Some(buffer) = &mut self.buffer {
+            buffer.read(|values, timestamp| func(values, timestamp.unwrap_or(now)))
+        } else {
+            if self
+                .polled
+                .map(|polled| now - polled < POLL_INTERVAL)
+                .unwrap_or_default()
+            {
+                return Ok(());
+            }
+            let values = self
+                .attributes
+                .iter()
+                .map(|attribute| self.device.attribute_value_typed_uncached(attribute))
+                .collect::<Option<Vec<f64>>>()
+                .ok_or_else(|| IioError::Poll(self.attributes.join(", ")))?;
+            self.polled = Some(now);
+            func(&values, now);
+            Ok(())
+        }
+    }
+
+    /// Start or stop sampling
+    pub fn set_active(&mut self, active: bool) {
+        if active == self.active {
+            return;
+        }
+        self.active = active;
+        self.polled = None;
+        if let Some(buffer) = &mut self.buffer {
+            if let Err(error) = buffer.set_enable(active) {
+                tracing::warn!("Unable to switch buffer: {error}");
+            }
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Whether sysfs polling is used
+    pub fn is_polling(&self) -> bool {
+        self.active && self.buffer.is_none()
+    }
+
+    /// Active buffer to wait for samples
+    pub fn buffer(&self) -> Option<&IioBuffer> {
+        self.buffer.as_ref().filter(|_| self.active)
+    }
+}
+
+/// IIO triggered buffer reader
+#[derive(Debug)]
+struct IioBuffer {
+    /// Associated device
+    device: udev::Device,
+    /// Character device to read samples from
+    file: Async<File>,
+    /// Requested channels
+    channels: Vec<ScanChannel>,
+    /// Timestamp channel, used only when it follows monotonic clock
+    timestamp: Option<ScanChannel>,
+    /// Sample size in bytes
+    sample_size: usize,
+    /// Read buffer
+    data: Vec<u8>,
+}
+
+impl IioBuffer {
+    /// Maximum number of samples to read at once
+    const MAX_SAMPLES: usize = 16;
+
+    /// Setup triggered buffer for channels
+    ///
+    /// Returns `None` when device does not support buffering.
+    pub fn new(device: &udev::Device, channels: &[&str], sampling_frequency: f64) -> Option<Self> {
+        match Self::setup(device.clone(), channels, sampling_frequency) {
+            Ok(Some(buffer)) => {
+                tracing::debug!("Use buffer for device {:?}", device.syspath());
+                Some(buffer)
+            }
+            Ok(None) => {
+                tracing::debug!("Buffer not supported by device {:?}", device.syspath());
+                None
+            }
+            Err(error) => {
+                tracing::warn!(
+                    "Unable to setup buffer for device {:?}: {error}",
+                    device.syspath()
+                );
+                None
+            }
+        }
+    }
+
+    fn setup(
+        mut device: udev::Device,
+        channels: &[&str],
+        sampling_frequency: f64,
+    ) -> Result<Option<Self>> {
+        let Some(devnode) = device.devnode().map(PathBuf::from) else {
+            return Ok(None);
+        };
+
+        if device.attribute_value("buffer/enable").is_none() {
+            return Ok(None);
+        }
+
+        // buffer must be disabled to change configuration
+        device.set_attribute_value("buffer/enable", "0")?;
+
+        // timestamps follows realtime clock by default which may jump,
+        // so samples is timed by service when monotonic clock cannot be selected
+        let monotonic = match device
+            .attribute_value_str("current_timestamp_clock")
+            .map(|clock| clock.trim() == "monotonic")
+        {
+            Some(false) => {
+                match device.set_attribute_value("current_timestamp_clock", "monotonic") {
+                    Ok(()) => true,
+                    Err(error) => {
+                        tracing::debug!("Unable to set timestamp clock: {error}");
+                        false
+                    }
+                }
+            }
+            Some(true) => true,
+            None => false,
+        };
+
+        let elements = std::fs::read_dir(device.syspath().join("scan_elements"))?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                entry
+                    .file_name()
+                    .to_str()
+                    .and_then(|name| name.strip_suffix("_en"))
+                    .map(String::from)
+            })
+            .collect::<Vec<_>>();
+
+        let mut enabled = Vec::new();
+
+        for element in &elements {
+            let enable = element == "in_timestamp" || channels.contains(&element.as_str());
+
+            device.set_attribute_value(
+                format!("scan_elements/{element}_en"),
+                if enable { "1" } else { "0" },
+            )?;
+
+            if enable {
+                let index: usize = device
+                    .attribute_value_typed(format!("scan_elements/{element}_index"))
+                    .ok_or_else(|| IioError::Buffer(format!("{element} index")))?;
+                let type_: ScanType = device
+                    .attribute_value_typed(format!("scan_elements/{element}_type"))
+                    .ok_or_else(|| IioError::Buffer(format!("{element} type")))?;
+                enabled.push((index, element.as_str(), type_));
+            }
+        }
+
+        if !channels
+            .iter()
+            .all(|channel| enabled.iter().any(|(_, element, _)| element == channel))
+        {
+            return Ok(None);
+        }
+
+        let (layout, sample_size) = scan_layout(enabled);
+
+        let find_channel = |name: &str| {
+            layout
+                .iter()
+                .find(|(element, _)| *element == name)
+                .map(|(_, channel)| *channel)
+        };
+
+        let timestamp = find_channel("in_timestamp").filter(|_| monotonic);
+        let channels = channels
+            .iter()
+            .filter_map(|name| find_channel(name))
+            .collect();
+
+        if device
+            .attribute_value_str("trigger/current_trigger")
+            .map(|trigger| trigger.trim().is_empty())
+            .unwrap_or_default()
+        {
+            if let (Some(name), Some(num)) = (device.attribute_value_str("name"), device.sysnum()) {
+                let trigger = format!("{name}-dev{num}");
+                if let Err(error) = device.set_attribute_value("trigger/current_trigger", &trigger)
+                {
+                    tracing::debug!("Unable to set trigger {trigger}: {error}");
+                }
+            }
+        }
+
+        if device.attribute_value("sampling_frequency").is_some() {
+            if let Err(error) =
+                device.set_attribute_value("sampling_frequency", sampling_frequency.to_string())
+            {
+                tracing::debug!("Unable to set sampling frequency: {error}");
+            }
+        }
+
+        device.set_attribute_value("buffer/enable", "1")?;
+
+        let file = Async::new(File::open(devnode)?)?;
+
+        Ok(Some(Self {
+            device,
+            file,
+            channels,
+            timestamp,
+            sample_size,
+            data: vec![0; sample_size * Self::MAX_SAMPLES],
+        }))
+    }
+
+    /// Enable or disable sampling
+    pub fn set_enable(&mut self, enable: bool) -> Result<()> {
+        self.device
+            .set_attribute_value("buffer/enable", if enable { "1" } else { "0" })?;
+        Ok(())
+    }
+
+    /// Wait until samples is available
+    pub async fn readable(&self) -> std::io::Result<()> {
+        self.file.readable().await
+    }
+
+    /// Read available samples
+    ///
+    /// The values of requested channels and timestamp passed to function for each sample.
+    pub fn read(&mut self, mut func: impl FnMut(&[f64], Option<Duration>)) -> Result<()> {
+        use std::io::Read;
+
+        let mut values = vec![0.0; self.channels.len()];
+
+        loop {
+            let len = match self.file.get_ref().read(&mut self.data) {
+                Ok(0) => break,
+                Ok(len) => len,
+                Err(error) if error.kind() == std::io::ErrorKind::WouldBlock => break,
+                Err(error) => return Err(error.into()),
+            };
+
+            for sample in self.data[..len].chunks_exact(self.sample_size) {
+                for (value, channel) in values.iter_mut().zip(&self.channels) {
+                    *value = channel.decode(sample) as f64;
+                }
+                let timestamp = self
+                    .timestamp
+                    .map(|channel| Duration::from_nanos(channel.decode(sample) as u64));
+                func(&values, timestamp);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for IioBuffer {
+    fn drop(&mut self) {
+        if let Err(error) = self.set_enable(false) {
+            tracing::warn!("Unable to disable buffer: {error}");
+        }
+    }
+}
+
+/// Place enabled scan elements in sample
+///
+/// Channels is placed by index and aligned to storage size,
+/// the sample is aligned to largest storage size.
+fn scan_layout(mut enabled: Vec<(usize, &str, ScanType)>) -> (Vec<(&str, ScanChannel)>, usize) {
+    enabled.sort_by_key(|(index, _, _)| *index);
+
+    let mut layout = Vec::new();
+    let mut offset = 0usize;
+    let mut align = 1;
+
+    for (_, element, type_) in enabled {
+        let storage_bytes = type_.storage_bytes();
+        offset = offset.next_multiple_of(storage_bytes);
+        layout.push((element, ScanChannel { offset, type_ }));
+        offset += type_.size();
+        align = align.max(storage_bytes);
+    }
+
+    (layout, offset.next_multiple_of(align))
+}
+
+/// Buffered channel layout
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct ScanChannel {
+    /// Byte offset in sample
+    offset: usize,
+    /// Element type
+    type_: ScanType,
+}
+
+impl ScanChannel {
+    fn decode(&self, sample: &[u8]) -> i64 {
+        self.type_.decode(&sample[self.offset..])
+    }
+}
+
+/// Scan element type like `le:s12/16>>4`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct ScanType {
+    big_endian: bool,
+    signed: bool,
+    bits: u32,
+    storage_bits: u32,
+    repeat: u32,
+    shift: u32,
+}
+
+impl ScanType {
+    fn storage_bytes(&self) -> usize {
+        (self.storage_bits / 8) as usize
+    }
+
+    fn size(&self) -> usize {
+        self.storage_bytes() * self.repeat as usize
+    }
+
+    fn decode(&self, data: &[u8]) -> i64 {
+        let data = &data[..self.storage_bytes()];
+
+        let raw = if self.big_endian {
+            data.iter().fold(0u64, |raw, byte| raw << 8 | *byte as u64)
+        } else {
+            data.iter()
+                .rev()
+                .fold(0u64, |raw, byte| raw << 8 | *byte as u64)
+        } >> self.shift;
+
+        if self.bits >= 64 {
+            return raw as i64;
+        }
+
+        let mask = (1u64 << self.bits) - 1;
+        let raw = raw & mask;
+
+        if self.signed && raw & (1 << (self.bits - 1)) != 0 {
+            (raw | !mask) as i64
+        } else {
+            raw as i64
+        }
+    }
+}
+
+impl FromStr for ScanType {
+    type Err = ();
+    fn from_str(s: &str) -> core::result::Result<Self, Self::Err> {
+        let (endian, s) = s.trim().split_once(':').ok_or(())?;
+        let big_endian = match endian {
+            "be" => true,
+            "le" => false,
+            _ => return Err(()),
+        };
+        let signed = match s.get(..1) {
+            Some("s" | "S") => true,
+            Some("u" | "U") => false,
+            _ => return Err(()),
+        };
+        let (bits, s) = s[1..].split_once('/').ok_or(())?;
+        let (storage, shift) = s.split_once(">>").ok_or(())?;
+        let (storage_bits, repeat) = storage.split_once('X').unwrap_or((storage, "1"));
+
+        let type_ = Self {
+            big_endian,
+            signed,
+            bits: bits.parse().map_err(|_| ())?,
+            storage_bits: storage_bits.parse().map_err(|_| ())?,
+            repeat: repeat.parse().map_err(|_| ())?,
+            shift: shift.parse().map_err(|_| ())?,
+        };
+
+        if type_.bits == 0
+            || !type_.storage_bits.is_multiple_of(8)
+            || type_.bits > type_.storage_bits
+        {
+            return Err(());
+        }
+
+        Ok(type_)
+    }
+}
+
+impl SensorConfig {
+    /// Whether overrides should be applied to device
+    fn matches(&self, device: &IioDevice) -> bool {
+        (self.name.is_some() || self.syspath.is_some() || !self.property.is_empty())
+            && self
+                .name
+                .as_ref()
+                .map(|name| device.attribute_value_str("name") == Some(name))
+                .unwrap_or(true)
+            && self
+                .syspath
+                .as_ref()
+                .map(|syspath| device.syspath() == syspath)
+                .unwrap_or(true)
+            && self
+                .property
+                .iter()
+                .all(|(property, value)| device.property_value_str(property) == Some(value))
+    }
+
+    fn mount_matrix(&self) -> Option<Mat3> {
+        let mount = parse_mount_matrix(self.mount_matrix.as_ref()?);
+        if mount.is_none() {
+            tracing::warn!("Invalid mount matrix: {:?}", self.mount_matrix);
+        }
+        mount
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(u8)]
+enum SensorType {
+    Accel,
+    Light,
+    Proximity,
+    Compass,
+}
+
+impl AsRef<str> for SensorType {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::Accel => "accel",
+            Self::Light => "light",
+            Self::Proximity => "proximity",
+            Self::Compass => "compass",
+        }
+    }
+}
+
+/// Sensor device
+#[derive(Clone, Debug)]
+enum IioDevice {
+    /// Device provided by udev
+    Udev(udev::Device),
+    /// Device emulated by directory tree
+    Fake(FakeDevice),
+}
+
+impl From<udev::Device> for IioDevice {
+    fn from(device: udev::Device) -> Self {
+        Self::Udev(device)
+    }
+}
+
+impl From<FakeDevice> for IioDevice {
+    fn from(device: FakeDevice) -> Self {
+        Self::Fake(device)
+    }
+}
+
+impl IioDevice {
+    /// Open device at path
+    ///
+    /// Emulated device is used when sysfs root is relocated.
+    fn from_syspath(path: impl AsRef<Path>, config: &IioConfig) -> Result<Self> {
+        Ok(if config.sysfs_root.is_some() {
+            FakeDevice::from_syspath(path)?.into()
+        } else {
+            udev::Device::from_syspath(path.as_ref())?.into()
+        })
+    }
+
+    fn syspath(&self) -> &Path {
+        match self {
+            Self::Udev(device) => device.syspath(),
+            Self::Fake(device) => device.syspath(),
+        }
     }
+}
+
+trait DeviceExt {
+    fn is_iio_device(&self) -> bool;
+
+    fn property_value_str(&self, property: impl AsRef<OsStr>) -> Option<&str>;
+
+    fn attribute_value_str(&self, attribute: impl AsRef<OsStr>) -> Option<&str>;
+    fn attribute_value_typed_uncached<T: FromStr>(&self, attribute: impl AsRef<OsStr>)
+        -> Option<T>;
 
-    fn attribute_value_uncache(&self, attribute: impl AsRef<OsStr>) -> std::io::Result<()> {
-        use udev::AsRawWithContext;
+    fn sensor_types(&self) -> Vec<SensorType> {
+        let mut types = Vec::new();
 
-        let attribute = util::os_str_to_cstring(attribute)?;
+        if !self.is_iio_device() {
+            return types;
+        }
 
-        util::errno_to_result(unsafe {
-            udev::ffi::udev_device_set_sysattr_value(
-                self.as_raw(),
-                attribute.as_ptr(),
-                core::ptr::null_mut() as *mut std::ffi::c_char,
-            )
-        })
+        if self
+            .attribute_value_str("name")
+            .map(|name| name.contains("accel"))
+            .unwrap_or_default()
+        {
+            types.push(SensorType::Accel);
+        }
+        if self.light_channel().is_some() {
+            types.push(SensorType::Light);
+        }
+        if self.proximity_channel().is_some() {
+            types.push(SensorType::Proximity);
+        }
+        if self.attribute_value_str("in_magn_x_raw").is_some()
+            || self
+                .attribute_value_str(format!("{HEADING_CHANNEL}_raw"))
+                .is_some()
+        {
+            types.push(SensorType::Compass);
+        }
+
+        types
     }
 
-    fn attribute_value_typed_uncached<T: FromStr>(
-        &self,
-        attribute: impl AsRef<OsStr>,
-    ) -> Option<T> {
-        self.attribute_value_uncache(attribute.as_ref()).ok()?;
+    fn property_value_typed<T: FromStr>(&self, property: impl AsRef<OsStr>) -> Option<T> {
+        self.property_value_str(property)
+            .and_then(|value| value.parse().ok())
+    }
+
+    fn attribute_value_typed<T: FromStr>(&self, attribute: impl AsRef<OsStr>) -> Option<T> {
         self.attribute_value_str(attribute)
             .and_then(|value| value.parse().ok())
     }
@@ -435,36 +2610,263 @@ impl DeviceExt for udev::Device {
             })
     }
 
-    fn accel_raw(&self) -> Option<Vec3> {
-        self.attribute_value_typed_uncached("in_accel_x_raw")
+    fn magn_mount_matrix(&self) -> Option<Mat3> {
+        self.property_value_str("MAGN_MOUNT_MATRIX")
+            .or_else(|| self.attribute_value_str("in_magn_mount_matrix"))
+            .or_else(|| self.attribute_value_str("mount_matrix"))
+            .or_else(|| self.attribute_value_str("in_mount_matrix"))
+            .and_then(parse_mount_matrix)
+    }
+
+    fn magn_offset(&self) -> Option<Vec3> {
+        self.attribute_value_typed("in_magn_x_offset")
+            .and_then(|x| {
+                self.attribute_value_typed("in_magn_y_offset")
+                    .map(|y| (x, y))
+            })
+            .and_then(|(x, y)| {
+                self.attribute_value_typed("in_magn_z_offset")
+                    .map(|z| vec3(x, y, z))
+            })
+            .or_else(|| {
+                self.attribute_value_typed("in_magn_offset")
+                    .map(|s| vec3(s, s, s))
+            })
+    }
+
+    fn magn_scale(&self) -> Option<Vec3> {
+        self.attribute_value_typed("in_magn_x_scale")
             .and_then(|x| {
-                self.attribute_value_typed_uncached("in_accel_y_raw")
+                self.attribute_value_typed("in_magn_y_scale")
                     .map(|y| (x, y))
             })
             .and_then(|(x, y)| {
-                self.attribute_value_typed_uncached("in_accel_z_raw")
+                self.attribute_value_typed("in_magn_z_scale")
                     .map(|z| vec3(x, y, z))
             })
+            .or_else(|| {
+                self.attribute_value_typed("in_magn_scale")
+                    .map(|s| vec3(s, s, s))
+            })
+    }
+
+    fn light_channel(&self) -> Option<&'static str> {
+        LIGHT_CHANNELS.iter().copied().find(|channel| {
+            self.attribute_value_str(format!("{channel}_raw")).is_some()
+                || self
+                    .attribute_value_str(format!("{channel}_input"))
+                    .is_some()
+        })
+    }
+
+    fn proximity_channel(&self) -> Option<&'static str> {
+        PROXIMITY_CHANNELS
+            .iter()
+            .copied()
+            .find(|channel| self.attribute_value_str(format!("{channel}_raw")).is_some())
     }
 }
 
-/// x1​, y1​, z1​; x2​, y2​, z2​; x3​, y3​, z3
-fn parse_mount_matrix(s: &str) -> Option<Mat3> {
-    let mut mat = [[0f64; 3]; 3];
+impl DeviceExt for udev::Device {
+    fn is_iio_device(&self) -> bool {
+        self.devtype()
+            .and_then(|type_| type_.to_str())
+            .filter(|type_| type_ == &"iio_device")
+            .is_some()
+    }
+
+    fn property_value_str(&self, property: impl AsRef<OsStr>) -> Option<&str> {
+        self.property_value(property)
+            .and_then(|value| value.to_str())
+    }
+
+    fn attribute_value_str(&self, attribute: impl AsRef<OsStr>) -> Option<&str> {
+        self.attribute_value(attribute)
+            .and_then(|value| value.to_str())
+    }
 
-    for (row, s) in s.split(';').enumerate() {
-        if row >= 3 {
-            break;
+    fn attribute_value_typed_uncached<T: FromStr>(
+        &self,
+        attribute: impl AsRef<OsStr>,
+    ) -> Option<T> {
+        use udev::AsRawWithContext;
+
+        let name = util::os_str_to_cstring(attribute.as_ref()).ok()?;
+
+        // drop cached value to read actual one
+        util::errno_to_result(unsafe {
+            udev::ffi::udev_device_set_sysattr_value(
+                self.as_raw(),
+                name.as_ptr(),
+                core::ptr::null_mut() as *mut std::ffi::c_char,
+            )
+        })
+        .ok()?;
+
+        self.attribute_value_str(attribute)
+            .and_then(|value| value.parse().ok())
+    }
+}
+
+impl DeviceExt for FakeDevice {
+    fn is_iio_device(&self) -> bool {
+        FakeDevice::is_iio_device(self)
+    }
+
+    fn property_value_str(&self, property: impl AsRef<OsStr>) -> Option<&str> {
+        self.property_value(property)
+    }
+
+    fn attribute_value_str(&self, attribute: impl AsRef<OsStr>) -> Option<&str> {
+        self.attribute_value(attribute)
+    }
+
+    fn attribute_value_typed_uncached<T: FromStr>(
+        &self,
+        attribute: impl AsRef<OsStr>,
+    ) -> Option<T> {
+        self.read_attribute(attribute)
+            .and_then(|value| value.parse().ok())
+    }
+}
+
+impl DeviceExt for IioDevice {
+    fn is_iio_device(&self) -> bool {
+        match self {
+            Self::Udev(device) => device.is_iio_device(),
+            Self::Fake(device) => DeviceExt::is_iio_device(device),
         }
-        for (col, s) in s.split(',').enumerate() {
-            if col >= 3 {
-                break;
-            }
-            mat[row][col] = s.trim().parse().ok()?;
+    }
+
+    fn property_value_str(&self, property: impl AsRef<OsStr>) -> Option<&str> {
+        match self {
+            Self::Udev(device) => device.property_value_str(property),
+            Self::Fake(device) => device.property_value_str(property),
+        }
+    }
+
+    fn attribute_value_str(&self, attribute: impl AsRef<OsStr>) -> Option<&str> {
+        match self {
+            Self::Udev(device) => device.attribute_value_str(attribute),
+            Self::Fake(device) => device.attribute_value_str(attribute),
+        }
+    }
+
+    fn attribute_value_typed_uncached<T: FromStr>(
+        &self,
+        attribute: impl AsRef<OsStr>,
+    ) -> Option<T> {
+        match self {
+            Self::Udev(device) => device.attribute_value_typed_uncached(attribute),
+            Self::Fake(device) => device.attribute_value_typed_uncached(attribute),
         }
     }
+}
+
+/// Convert raw channel value to processed one
+///
+/// IIO ABI defines processed value as `(raw + offset) * scale`.
+fn processed<T: core::ops::Add<Output = T> + core::ops::Mul<Output = T>>(
+    raw: T,
+    offset: T,
+    scale: T,
+) -> T {
+    (raw + offset) * scale
+}
+
+/// Estimate hinge angle using gravity vectors of base and display sensors
+///
+/// The hinge axis is assumed to be X for both sensors. Gravity vectors are
+/// projected onto YZ plane and the angle between projections gives hinge
+/// angle in range 0..2π, where 0 means closed lid and π means flat open.
+/// When the hinge axis is close to vertical the projections degenerate,
+/// so `None` is returned if any gravity vector is closer to the hinge axis
+/// than `min_tilt`.
+fn hinge_angle(base: &Vec3, display: &Vec3, min_tilt: f64) -> Option<f64> {
+    let tilt = |value: &Vec3| FRAC_PI_2 - (value.angle_between(Vec3::X) - FRAC_PI_2).abs();
+
+    if tilt(base) < min_tilt || tilt(display) < min_tilt {
+        return None;
+    }
+
+    let base_angle = base.z.atan2(base.y);
+    let display_angle = display.z.atan2(display.y);
+
+    Some((PI + display_angle - base_angle).rem_euclid(TAU))
+}
+
+/// Whether gravity vector in device frame matches calibration pose
+///
+/// The dominant axis and its sign should correspond to pose and the
+/// magnitude should be close to standard gravity.
+fn pose_matches(pose: CalibrationPose, gravity: Vec3) -> bool {
+    // same conventions as orientation and flat detection
+    let expected = match pose {
+        CalibrationPose::TopUp => Vec3::NEG_Y,
+        CalibrationPose::BottomUp => Vec3::Y,
+        CalibrationPose::LeftUp => Vec3::X,
+        CalibrationPose::RightUp => Vec3::NEG_X,
+        CalibrationPose::FaceUp => Vec3::NEG_Z,
+        CalibrationPose::FaceDown => Vec3::Z,
+    };
+
+    let abs = gravity.abs();
+    let dominant = if abs.x >= abs.y && abs.x >= abs.z {
+        Vec3::X * gravity.x.signum()
+    } else if abs.y >= abs.z {
+        Vec3::Y * gravity.y.signum()
+    } else {
+        Vec3::Z * gravity.z.signum()
+    };
+
+    dominant == expected
+        && (gravity.length() / STANDARD_GRAVITY - 1.0).abs() < CALIBRATION_MAX_GRAVITY_ERROR
+}
+
+/// Compute accelerometer corrections using values sampled in six poses
+///
+/// Each axis is expected to see `+g` and `-g` in some poses, so the middle
+/// of the sampled range gives the offset and half of the range gives the
+/// scale. Returns `None` when the range is degenerate for any axis.
+fn accel_calibration(values: impl Iterator<Item = Vec3> + Clone) -> Option<AccelCalibration> {
+    let min = values.clone().fold(Vec3::INFINITY, Vec3::min);
+    let max = values.fold(Vec3::NEG_INFINITY, Vec3::max);
+    let range = (max - min) * 0.5;
+
+    if !range.is_finite() || range.min_element() < f64::EPSILON {
+        return None;
+    }
+
+    Some(AccelCalibration {
+        offset: ((max + min) * 0.5).to_array(),
+        scale: (Vec3::splat(STANDARD_GRAVITY) / range).to_array(),
+    })
+}
+
+/// Compute compass heading using gravity and magnetic field vectors
+///
+/// The heading is measured for device Y axis when it lies flat and for
+/// device back (negative Z axis) when it stands upright. Returns heading
+/// in degrees from 0 to 360 or `None` when vectors are collinear.
+fn tilt_compensated_heading(gravity: &Vec3, field: &Vec3) -> Option<f64> {
+    let down = gravity.try_normalize()?;
+    let east = down.cross(*field).try_normalize()?;
+    let north = east.cross(down);
+
+    let axis = if down.y.abs() > down.z.abs() {
+        Vec3::NEG_Z
+    } else {
+        Vec3::Y
+    };
 
-    Some(Mat3::from_cols_array_2d(&mat))
+    let heading = east.dot(axis).atan2(north.dot(axis)).to_degrees();
+
+    Some(heading.rem_euclid(360.0))
+}
+
+/// x1​, y1​, z1​; x2​, y2​, z2​; x3​, y3​, z3
+fn parse_mount_matrix(s: &str) -> Option<Mat3> {
+    crate::parse_matrix(s).map(|mat| Mat3::from_cols_array_2d(&mat))
 }
 
 mod util {
@@ -485,4 +2887,568 @@ mod util {
             Ok(())
         }
     }
+
+    /// Current time of monotonic clock which is used for buffer timestamps
+    pub fn monotonic_time() -> std::time::Duration {
+        let mut ts = libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+        // cannot fail with valid clock id and pointer
+        unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut ts) };
+        std::time::Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRACES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data");
+
+    /// Create fake accelerometer device directory
+    fn fake_accel(root: &Path, name: &str, label: &str, raw: [i32; 3]) -> PathBuf {
+        let path = root.join(name);
+        std::fs::create_dir(&path).unwrap();
+        std::fs::write(path.join("name"), "accel_3d\n").unwrap();
+        std::fs::write(path.join("label"), format!("{label}\n")).unwrap();
+        std::fs::write(path.join("in_accel_scale"), "0.01\n").unwrap();
+        set_raw(&path, raw);
+        path
+    }
+
+    fn set_raw(path: &Path, raw: [i32; 3]) {
+        for (channel, value) in ACCEL_CHANNELS.iter().zip(raw) {
+            std::fs::write(path.join(format!("{channel}_raw")), format!("{value}\n")).unwrap();
+        }
+    }
+
+    /// Poll sensors several times as fast as readers allow
+    fn poll(iio: &mut Iio, time: &mut Duration, count: usize) {
+        for _ in 0..count {
+            *time += POLL_INTERVAL;
+            iio.poll_at(*time).unwrap();
+        }
+    }
+
+    /// Gravity of upright display turned clockwise in its plane by angle in degrees
+    fn turned(angle: f64) -> [f64; 3] {
+        let angle = angle.to_radians();
+        [-9.81 * angle.sin(), -9.81 * angle.cos(), 0.0]
+    }
+
+    /// Push the same display samples at 10 Hz during duration in seconds
+    fn hold(iio: &mut Iio, time: &mut Duration, value: [f64; 3], duration: f64) {
+        for _ in 0..(duration * 10.0).round() as usize {
+            *time += Duration::from_millis(100);
+            iio.push_accel(AccelLocation::Display, value, *time);
+        }
+    }
+
+    /// Push base and display samples of device opened to hinge angle in degrees
+    ///
+    /// Base is turned around hinge axis by tilt in degrees from lying on the table.
+    fn open(iio: &mut Iio, time: &mut Duration, hinge: f64, tilt: f64) {
+        let base = tilt.to_radians() - FRAC_PI_2;
+        let display = hinge.to_radians() - PI + base;
+        *time += Duration::from_millis(100);
+        for (location, angle) in [
+            (AccelLocation::Base, base),
+            (AccelLocation::Display, display),
+        ] {
+            iio.push_accel(
+                location,
+                [0.0, 9.81 * angle.cos(), 9.81 * angle.sin()],
+                *time,
+            );
+        }
+    }
+
+    fn hinge_degrees(iio: &Iio) -> Option<f64> {
+        iio.hinge_angle()
+            .map(|angle| (angle.to_degrees() * 1e6).round() / 1e6)
+    }
+
+    /// Replay trace and get detected changes
+    fn replay(name: &str) -> Vec<(Duration, TraceEvent)> {
+        let mut output = Vec::new();
+        Iio::replay(
+            Path::new(TRACES).join(name),
+            &Config::default(),
+            &mut output,
+        )
+        .unwrap();
+
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| {
+                let (time, event) = line.split_once(' ').unwrap();
+                (
+                    Duration::from_secs_f64(time.parse().unwrap()),
+                    event.parse().unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn calibration_math() {
+        let g = STANDARD_GRAVITY;
+        let offset = Vec3::new(0.2, -0.1, 0.3);
+        let scale = Vec3::new(1.1, 0.9, 1.0);
+        // sensor reports (true + offset) / scale
+        let poses = [
+            Vec3::new(0.0, g, 0.0),
+            Vec3::new(0.0, -g, 0.0),
+            Vec3::new(g, 0.0, 0.0),
+            Vec3::new(-g, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, g),
+            Vec3::new(0.0, 0.0, -g),
+        ]
+        .map(|value| (value + offset) / scale);
+
+        let calibration = accel_calibration(poses.into_iter()).unwrap();
+        let cal_offset = Vec3::from_array(calibration.offset);
+        let cal_scale = Vec3::from_array(calibration.scale);
+        assert!(cal_offset.abs_diff_eq(offset / scale, 1e-9));
+        assert!(cal_scale.abs_diff_eq(scale, 1e-9));
+
+        for pose in poses {
+            let value = (pose - cal_offset) * cal_scale;
+            assert!((value.length() - g).abs() < 1e-9);
+        }
+
+        // identity when sensor is already calibrated
+        let calibration = accel_calibration(poses.map(|value| value * scale - offset).into_iter());
+        let calibration = calibration.unwrap();
+        assert!(Vec3::from_array(calibration.offset).abs_diff_eq(Vec3::ZERO, 1e-9));
+        assert!(Vec3::from_array(calibration.scale).abs_diff_eq(Vec3::ONE, 1e-9));
+
+        // missing poses for some axis
+        assert!(accel_calibration(poses[..4].iter().copied()).is_none());
+        assert!(accel_calibration([Vec3::ZERO; 6].into_iter()).is_none());
+        assert!(accel_calibration(core::iter::empty()).is_none());
+    }
+
+    #[test]
+    fn calibration_pose() {
+        let g = STANDARD_GRAVITY;
+
+        assert!(pose_matches(CalibrationPose::TopUp, vec3(0.5, -g, 1.0)));
+        assert!(pose_matches(
+            CalibrationPose::LeftUp,
+            vec3(g * 1.1, 0.0, 0.0)
+        ));
+        assert!(pose_matches(
+            CalibrationPose::FaceUp,
+            vec3(0.0, 0.0, -g * 0.9)
+        ));
+        assert!(pose_matches(CalibrationPose::FaceDown, vec3(0.0, 0.0, g)));
+
+        // same pose sampled under other label
+        assert!(!pose_matches(CalibrationPose::FaceDown, vec3(0.0, 0.0, -g)));
+        assert!(!pose_matches(CalibrationPose::BottomUp, vec3(0.0, -g, 0.0)));
+        // device held between poses
+        assert!(!pose_matches(
+            CalibrationPose::RightUp,
+            vec3(-g, g * 1.1, 0.0)
+        ));
+        // wrong magnitude
+        assert!(!pose_matches(
+            CalibrationPose::TopUp,
+            vec3(0.0, -g * 2.0, 0.0)
+        ));
+        assert!(!pose_matches(CalibrationPose::TopUp, Vec3::ZERO));
+    }
+
+    #[test]
+    fn scan_type() {
+        let type_: ScanType = "le:s12/16>>4".parse().unwrap();
+        assert_eq!(
+            type_,
+            ScanType {
+                big_endian: false,
+                signed: true,
+                bits: 12,
+                storage_bits: 16,
+                repeat: 1,
+                shift: 4,
+            }
+        );
+        // 12-bit samples in upper bits
+        assert_eq!(type_.decode(&[0xf0, 0x7f]), 0x7ff);
+        assert_eq!(type_.decode(&[0x00, 0x80]), -0x800);
+        assert_eq!(type_.decode(&[0xf0, 0xff]), -1);
+
+        let type_: ScanType = "be:u12/16>>0".parse().unwrap();
+        assert_eq!(type_.decode(&[0xff, 0xff]), 0xfff);
+
+        let type_: ScanType = "be:S32/32>>0".parse().unwrap();
+        assert_eq!(type_.decode(&[0xff, 0xff, 0xff, 0xfe]), -2);
+
+        let type_: ScanType = "le:s64/64>>0\n".parse().unwrap();
+        assert_eq!(type_.decode(&(-5i64).to_le_bytes()), -5);
+
+        let type_: ScanType = "be:u16/32X3>>0".parse().unwrap();
+        assert_eq!(
+            (type_.repeat, type_.storage_bytes(), type_.size()),
+            (3, 4, 12)
+        );
+
+        for type_ in [
+            "",
+            "le:s12/16",
+            "me:s12/16>>0",
+            "le:f12/16>>0",
+            "le:s0/16>>0",
+            "le:s12/12>>0",
+            "le:s24/16>>0",
+            "le:s12/16X>>0",
+        ] {
+            assert!(type_.parse::<ScanType>().is_err(), "{type_:?}");
+        }
+    }
+
+    #[test]
+    fn buffer_layout() {
+        let s16: ScanType = "le:s16/16>>0".parse().unwrap();
+        let s64: ScanType = "le:s64/64>>0".parse().unwrap();
+
+        // timestamp aligned to its size, sample aligned to timestamp
+        let (layout, sample_size) = scan_layout(vec![
+            (3, "in_timestamp", s64),
+            (1, "in_accel_y", s16),
+            (0, "in_accel_x", s16),
+            (2, "in_accel_z", s16),
+        ]);
+        let channel = |offset, type_| ScanChannel { offset, type_ };
+        assert_eq!(
+            layout,
+            [
+                ("in_accel_x", channel(0, s16)),
+                ("in_accel_y", channel(2, s16)),
+                ("in_accel_z", channel(4, s16)),
+                ("in_timestamp", channel(8, s64)),
+            ]
+        );
+        assert_eq!(sample_size, 16);
+
+        let mut sample = [0u8; 16];
+        sample[2..4].copy_from_slice(&(-980i16).to_le_bytes());
+        sample[8..].copy_from_slice(&123456789i64.to_le_bytes());
+        assert_eq!(layout[1].1.decode(&sample), -980);
+        assert_eq!(layout[3].1.decode(&sample), 123456789);
+
+        // repeated element takes several storages
+        let u8_: ScanType = "le:u8/8>>0".parse().unwrap();
+        let u32x2: ScanType = "le:u32/32X2>>0".parse().unwrap();
+        let (layout, sample_size) =
+            scan_layout(vec![(0, "in_a", u8_), (1, "in_b", u32x2), (2, "in_c", u8_)]);
+        assert_eq!(
+            layout,
+            [
+                ("in_a", channel(0, u8_)),
+                ("in_b", channel(4, u32x2)),
+                ("in_c", channel(12, u8_)),
+            ]
+        );
+        assert_eq!(sample_size, 16);
+    }
+
+    #[test]
+    fn fake_sysfs() {
+        let root = tempfile::tempdir().unwrap();
+        // laptop opened to right angle
+        let display = fake_accel(root.path(), "iio:device0", "accel-display", [0, -980, 0]);
+        let base = fake_accel(root.path(), "iio:device1", "accel-base", [0, 0, -980]);
+
+        let mut config = Config::default();
+        config.iio.sysfs_root = Some(root.path().into());
+
+        let devices = config.find_iio_devices().unwrap();
+        assert_eq!(devices, [display.clone(), base.clone()]);
+
+        let mut iio = Iio::from_paths(&devices, &config).unwrap();
+        assert!(iio.has_orientation());
+
+        // acceleration needs three samples
+        let mut time = Duration::ZERO;
+        poll(&mut iio, &mut time, 3);
+
+        assert_eq!(iio.display_orientation(), Some(Orientation::TopUp));
+        assert_eq!(iio.display_flat(), Some((false, false)));
+        assert_eq!(iio.tablet_mode(), Some(false));
+        assert_eq!(iio.posture(), Some(Posture::Laptop));
+
+        // keyboard folded back to stand
+        set_raw(&base, [0, 0, 980]);
+        poll(&mut iio, &mut time, 1);
+
+        assert_eq!(iio.display_orientation(), Some(Orientation::TopUp));
+        assert_eq!(iio.tablet_mode(), Some(true));
+        assert_eq!(
+            iio.hinge_angle().map(|angle| angle.to_degrees().round()),
+            Some(270.0)
+        );
+    }
+
+    #[test]
+    fn replay_rotation() {
+        let changes = replay("rotate-left.trace");
+
+        let orientations = changes
+            .iter()
+            .filter_map(|(time, event)| match event {
+                TraceEvent::Orientation(orient) => Some((*time, *orient)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(orientations.len(), 2);
+        assert_eq!(orientations[0].1, Orientation::TopUp);
+        assert_eq!(orientations[1].1, Orientation::LeftUp);
+        // device turned at 4 sec
+        assert!(orientations[1].0 > Duration::from_secs(4));
+
+        assert!(changes.contains(&(
+            changes[0].0,
+            TraceEvent::Flat {
+                flat: false,
+                facing_up: false
+            }
+        )));
+    }
+
+    #[test]
+    fn replay_raw_samples() {
+        // same rotation recorded from upside down mounted sensor
+        let changes = replay("rotate-left-raw.trace");
+        assert_eq!(changes, replay("rotate-left.trace"));
+
+        // calibration from config is applied to raw samples
+        let mut config = Config::default();
+        config.calibration.display = Some(AccelCalibration {
+            offset: [0.0; 3],
+            scale: [-1.0, -1.0, 1.0],
+        });
+        let mut output = Vec::new();
+        Iio::replay(
+            Path::new(TRACES).join("rotate-left-raw.trace"),
+            &config,
+            &mut output,
+        )
+        .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("orientation bottom-up"));
+        assert!(output.contains("orientation right-up"));
+    }
+
+    #[test]
+    fn processed_value() {
+        assert_eq!(processed(100.0, -20.0, 0.5), 40.0);
+
+        // sysfs offset is added to raw value before scale
+        let mut iio = Iio::new(&Config::default());
+        iio.replay_accel(
+            AccelLocation::Display,
+            Mat3::IDENTITY,
+            vec3(10.0, -10.0, 0.0),
+            Vec3::splat(0.5),
+        );
+        iio.push_raw_accel(AccelLocation::Display, [0.0, 10.0, 19.62], Duration::ZERO);
+        let data = iio.display_accel_data().unwrap();
+        assert_eq!([data.x, data.y, data.z], [5.0, 0.0, 9.81]);
+    }
+
+    #[test]
+    fn orientation_debounce() {
+        let mut config = Config::default();
+        config.orientation.filter = OrientationFilter::None;
+        // sudden turns is allowed to check debounce alone
+        config.orientation.max_velocity = 1e6;
+        config.orientation.max_acceleration = 1e6;
+        // wide tolerance to check margin past boundary
+        config.orientation.max_xy_angle = 45.0;
+        config.orientation.hysteresis = 10.0;
+        config.orientation.stable_time = 0.5;
+
+        let mut iio = Iio::new(&config);
+        let mut time = Duration::ZERO;
+
+        // first orientation is taken once derivatives is estimated
+        hold(&mut iio, &mut time, turned(0.0), 2.0);
+        assert_eq!(iio.display_orientation(), Some(Orientation::TopUp));
+
+        // past boundary but within margin
+        hold(&mut iio, &mut time, turned(50.0), 2.0);
+        assert_eq!(iio.display_orientation(), Some(Orientation::TopUp));
+
+        // past margin but shorter than stable time
+        hold(&mut iio, &mut time, turned(60.0), 0.3);
+        assert_eq!(iio.display_orientation(), Some(Orientation::TopUp));
+        hold(&mut iio, &mut time, turned(0.0), 0.1);
+        hold(&mut iio, &mut time, turned(60.0), 0.3);
+        assert_eq!(iio.display_orientation(), Some(Orientation::TopUp));
+
+        // held for stable time since first sample
+        hold(&mut iio, &mut time, turned(60.0), 0.3);
+        assert_eq!(iio.display_orientation(), Some(Orientation::RightUp));
+
+        // margin is kept around new orientation too
+        hold(&mut iio, &mut time, turned(40.0), 2.0);
+        assert_eq!(iio.display_orientation(), Some(Orientation::RightUp));
+        hold(&mut iio, &mut time, turned(-20.0), 1.0);
+        assert_eq!(iio.display_orientation(), Some(Orientation::TopUp));
+    }
+
+    #[test]
+    fn samples_filter() {
+        // moving average reaches 1 - 1/e of step during time constant
+        let mut config = OrientationConfig {
+            filter: OrientationFilter::Ema,
+            filter_time: 0.2,
+            ..Default::default()
+        };
+        let mut filter = Filter::new(&config);
+        assert_eq!(filter.apply(Vec3::ZERO, 0.0), Vec3::ZERO);
+        let value = filter.apply(Vec3::ONE, 0.2);
+        assert!(value.abs_diff_eq(Vec3::splat(1.0 - (-1.0f64).exp()), 1e-9));
+        filter.reset();
+        assert_eq!(filter.apply(Vec3::ONE, 0.0), Vec3::ONE);
+
+        // Kalman filter trusts steady value more than single outlier
+        config.filter = OrientationFilter::Kalman;
+        config.process_noise = 0.01;
+        config.measurement_noise = 0.25;
+        let mut filter = Filter::new(&config);
+        for _ in 0..50 {
+            filter.apply(Vec3::ZERO, 0.1);
+        }
+        let value = filter.apply(Vec3::ONE, 0.1);
+        assert!(value.x > 0.0 && value.x < 0.2, "{value}");
+        // but follows value which is changed for real
+        for _ in 0..50 {
+            filter.apply(Vec3::ONE, 0.1);
+        }
+        assert!(filter.apply(Vec3::ONE, 0.1).abs_diff_eq(Vec3::ONE, 0.05));
+
+        // single noisy sample does not turn filtered gravity
+        let mut config = Config::default();
+        config.orientation.filter = OrientationFilter::Ema;
+        let mut iio = Iio::new(&config);
+        let mut time = Duration::ZERO;
+        hold(&mut iio, &mut time, turned(0.0), 2.0);
+        hold(&mut iio, &mut time, turned(90.0), 0.1);
+        let data = iio.display_accel_data().unwrap();
+        let angle = (-data.x).atan2(-data.y).to_degrees();
+        assert!(angle < 45.0, "{angle}");
+        hold(&mut iio, &mut time, turned(0.0), 1.0);
+        assert_eq!(iio.display_orientation(), Some(Orientation::TopUp));
+
+        // the same sample is taken as is without filter
+        config.orientation.filter = OrientationFilter::None;
+        let mut iio = Iio::new(&config);
+        let mut time = Duration::ZERO;
+        hold(&mut iio, &mut time, turned(0.0), 2.0);
+        hold(&mut iio, &mut time, turned(90.0), 0.1);
+        let data = iio.display_accel_data().unwrap();
+        let angle = (-data.x).atan2(-data.y).to_degrees();
+        assert!((angle - 90.0).abs() < 1e-9, "{angle}");
+    }
+
+    #[test]
+    fn hinge_hysteresis() {
+        let mut config = Config::default();
+        config.orientation.filter = OrientationFilter::None;
+        config.tablet_mode.hinge_angle = 200.0;
+        config.tablet_mode.hinge_hysteresis = 20.0;
+
+        let mut iio = Iio::new(&config);
+        let mut time = Duration::ZERO;
+
+        open(&mut iio, &mut time, 90.0, 0.0);
+        assert_eq!(hinge_degrees(&iio), Some(90.0));
+        assert_eq!(iio.tablet_mode(), Some(false));
+
+        open(&mut iio, &mut time, 210.0, 0.0);
+        assert_eq!(iio.tablet_mode(), Some(true));
+        // tablet mode is kept until angle falls below threshold minus hysteresis
+        open(&mut iio, &mut time, 190.0, 0.0);
+        assert_eq!(iio.tablet_mode(), Some(true));
+        open(&mut iio, &mut time, 170.0, 0.0);
+        assert_eq!(iio.tablet_mode(), Some(false));
+        // and laptop mode is kept until threshold
+        open(&mut iio, &mut time, 195.0, 0.0);
+        assert_eq!(iio.tablet_mode(), Some(false));
+
+        // readings is ignored when hinge axis is near to vertical
+        time += Duration::from_millis(100);
+        iio.push_accel(AccelLocation::Base, [9.81, 0.0, 0.5], time);
+        iio.push_accel(AccelLocation::Display, [9.81, 0.5, 0.0], time);
+        assert_eq!(hinge_degrees(&iio), Some(195.0));
+    }
+
+    #[test]
+    fn hinge_seam() {
+        let mut config = Config::default();
+        config.orientation.filter = OrientationFilter::None;
+
+        // fully folded device cannot be distinguished from closed one by gravity,
+        // so angle is kept on the side of the seam it came from
+        let mut iio = Iio::new(&config);
+        let mut time = Duration::ZERO;
+        open(&mut iio, &mut time, 350.0, 0.0);
+        assert_eq!(hinge_degrees(&iio), Some(350.0));
+        open(&mut iio, &mut time, 10.0, 0.0);
+        assert_eq!(hinge_degrees(&iio), Some(360.0));
+        assert_eq!(iio.tablet_mode(), Some(true));
+        open(&mut iio, &mut time, 300.0, 0.0);
+        assert_eq!(hinge_degrees(&iio), Some(300.0));
+
+        let mut iio = Iio::new(&config);
+        let mut time = Duration::ZERO;
+        open(&mut iio, &mut time, 10.0, 0.0);
+        assert_eq!(hinge_degrees(&iio), Some(10.0));
+        open(&mut iio, &mut time, 350.0, 0.0);
+        assert_eq!(hinge_degrees(&iio), Some(0.0));
+        assert_eq!(iio.tablet_mode(), Some(false));
+        open(&mut iio, &mut time, 60.0, 0.0);
+        assert_eq!(hinge_degrees(&iio), Some(60.0));
+    }
+
+    #[test]
+    fn posture() {
+        let mut config = Config::default();
+        config.orientation.filter = OrientationFilter::None;
+        config.tablet_mode.closed_angle = 20.0;
+        config.tablet_mode.folded_angle = 300.0;
+
+        let mut iio = Iio::new(&config);
+        let mut time = Duration::ZERO;
+
+        open(&mut iio, &mut time, 10.0, 0.0);
+        assert_eq!(iio.posture(), Some(Posture::Closed));
+        open(&mut iio, &mut time, 110.0, 0.0);
+        assert_eq!(iio.posture(), Some(Posture::Laptop));
+        // keyboard faces down to the table
+        open(&mut iio, &mut time, 270.0, 180.0);
+        assert_eq!(iio.posture(), Some(Posture::Stand));
+        // both halves stands on its edges
+        open(&mut iio, &mut time, 270.0, 120.0);
+        assert_eq!(iio.posture(), Some(Posture::Tent));
+        // base and display samples is pushed one by one, so device is moved gradually
+        // to not cross the seam between closed and folded states
+        for step in 1..=10 {
+            let step = step as f64 / 10.0;
+            open(
+                &mut iio,
+                &mut time,
+                270.0 + 60.0 * step,
+                120.0 * (1.0 - step),
+            );
+        }
+        assert_eq!(hinge_degrees(&iio), Some(330.0));
+        assert_eq!(iio.posture(), Some(Posture::Tablet));
+    }
 }
@@ -0,0 +1,106 @@
+use crate::Result;
+use std::{
+    collections::HashMap,
+    ffi::OsStr,
+    path::{Path, PathBuf},
+};
+
+/// Sensor device emulated by directory
+///
+/// The attributes is regular files in device directory and udev properties
+/// is `KEY=VALUE` lines of `uevent` file, like in sysfs.
+#[derive(Clone, Debug)]
+pub struct FakeDevice {
+    syspath: PathBuf,
+    sysname: String,
+    /// Attribute values read when device added
+    attributes: HashMap<String, String>,
+    properties: HashMap<String, String>,
+}
+
+impl FakeDevice {
+    /// Load device from directory
+    pub fn from_syspath(syspath: impl AsRef<Path>) -> Result<Self> {
+        let syspath = syspath.as_ref().to_path_buf();
+        let sysname = syspath
+            .file_name()
+            .and_then(OsStr::to_str)
+            .unwrap_or_default()
+            .to_string();
+
+        let mut attributes = HashMap::new();
+        for entry in std::fs::read_dir(&syspath)? {
+            let entry = entry?;
+            if !entry.path().is_file() {
+                continue;
+            }
+            let Some(name) = entry.file_name().to_str().map(String::from) else {
+                continue;
+            };
+            if let Ok(value) = std::fs::read_to_string(entry.path()) {
+                attributes.insert(name, value.trim_end().to_string());
+            }
+        }
+
+        let properties = attributes
+            .get("uevent")
+            .map(|uevent| {
+                uevent
+                    .lines()
+                    .filter_map(|line| line.split_once('='))
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(Self {
+            syspath,
+            sysname,
+            attributes,
+            properties,
+        })
+    }
+
+    /// Find devices in directory which mimics `/sys/bus/iio/devices`
+    pub fn enumerate(root: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
+        let mut paths = std::fs::read_dir(root)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .collect::<Vec<_>>();
+        paths.sort();
+        Ok(paths)
+    }
+
+    pub fn syspath(&self) -> &Path {
+        &self.syspath
+    }
+
+    /// Whether device is IIO device rather than trigger
+    pub fn is_iio_device(&self) -> bool {
+        self.property_value("DEVTYPE")
+            .map(|type_| type_ == "iio_device")
+            .unwrap_or_else(|| self.sysname.starts_with("iio:device"))
+    }
+
+    pub fn property_value(&self, property: impl AsRef<OsStr>) -> Option<&str> {
+        self.properties
+            .get(property.as_ref().to_str()?)
+            .map(String::as_str)
+    }
+
+    pub fn attribute_value(&self, attribute: impl AsRef<OsStr>) -> Option<&str> {
+        self.attributes
+            .get(attribute.as_ref().to_str()?)
+            .map(String::as_str)
+    }
+
+    /// Read current attribute value
+    ///
+    /// Used for sample values which changes after device added.
+    pub fn read_attribute(&self, attribute: impl AsRef<OsStr>) -> Option<String> {
+        std::fs::read_to_string(self.syspath.join(attribute.as_ref()))
+            .ok()
+            .map(|value| value.trim_end().to_string())
+    }
+}
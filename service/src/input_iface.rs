@@ -1,13 +1,17 @@
-use crate::{Config, Result, Service};
+use crate::{
+    Config, DeviceConfig, InputDeviceReport, Result, Service, TabletModeSource, TraceEvent,
+    TraceSwitch,
+};
 use input::{
     event::{Event, EventTrait},
     Device, Libinput, LibinputInterface,
 };
 use smol::Async;
 use std::{
+    collections::HashSet,
     fs::{File, OpenOptions},
     os::unix::{fs::OpenOptionsExt, io::OwnedFd},
-    path::{Path, PathBuf},
+    path::Path,
 };
 
 /// Input error type
@@ -16,16 +20,12 @@ pub enum InputError {
     /// Add seat
     #[error("Add seat: {0}")]
     AddSeat(String),
-    /// Add path
-    #[error("Add path: {0}")]
-    AddPath(PathBuf),
 }
 
 impl AsRef<str> for InputError {
     fn as_ref(&self) -> &str {
         match self {
             Self::AddSeat(_) => "input-add-seat",
-            Self::AddPath(_) => "input-add-path",
         }
     }
 }
@@ -61,36 +61,6 @@ impl Input {
         Ok(this)
     }
 
-    pub fn from_paths(paths: impl IntoIterator<Item = impl AsRef<Path>>) -> Result<Self> {
-        let mut this = Self(Async::new(Libinput::new_from_path(InputInterface))?);
-
-        for path in paths {
-            let path = path.as_ref();
-            if let Some(path_str) = path.to_str() {
-                this.path_add_device(path_str)
-                    .ok_or_else(|| InputError::AddPath(path.into()))?;
-            }
-        }
-
-        this.dispatch()?;
-
-        Ok(this)
-    }
-
-    pub fn devices(&mut self) -> Result<impl Iterator<Item = Device> + '_> {
-        use input::event::DeviceEvent;
-
-        self.dispatch()?;
-
-        Ok((&mut **self).filter_map(move |event| {
-            if let Event::Device(DeviceEvent::Added(event)) = &event {
-                Some(event.device())
-            } else {
-                None
-            }
-        }))
-    }
-
     pub async fn wait(&mut self) -> Result<()> {
         self.0.readable().await?;
         self.dispatch()?;
@@ -98,18 +68,23 @@ impl Input {
     }
 
     pub async fn process(
-        devices: Vec<PathBuf>,
         service: Service,
+        config: &Config,
     ) -> Result<Option<async_signal::Signal>> {
-        use input::{
-            event::{
-                switch::{Switch, SwitchState},
-                DeviceEvent, SwitchEvent,
-            },
-            DeviceCapability,
+        use input::event::{
+            switch::{Switch, SwitchState},
+            DeviceEvent, SwitchEvent,
         };
 
-        let mut input = Self::from_paths(devices)?;
+        let mut input = Self::from_udev(config.udev.iter().map(|cfg| &cfg.seat))?;
+        // sysnames of used tablet-mode switches
+        let mut switches = HashSet::new();
+        // sysnames of used lid switches
+        let mut lid_switches = HashSet::new();
+        // sysnames of attached keyboard docks
+        let mut docks = HashSet::new();
+        let mut last_docked = None;
+        let mut enumerated = false;
 
         loop {
             for event in &mut *input {
@@ -117,25 +92,113 @@ impl Input {
                 match event {
                     Event::Device(DeviceEvent::Added(event)) => {
                         let device = event.device();
-                        if device.has_capability(DeviceCapability::Switch)
-                            && device
-                                .switch_has_switch(Switch::TabletMode)
-                                .unwrap_or(false)
-                        {
-                            service.set_tablet_mode(false).await?;
+                        if config.is_switch(&device, Switch::TabletMode) {
+                            tracing::info!("Use input device: {device:?}");
+                            switches.insert(device.sysname().to_string());
+                            // libinput sends toggle event when switch is on already,
+                            // state kept when switch is re-added on reload
+                            if switches.len() == 1
+                                && service
+                                    .source_tablet_mode(TabletModeSource::Switch)
+                                    .await
+                                    .is_none()
+                            {
+                                service
+                                    .set_tablet_mode(TabletModeSource::Switch, false)
+                                    .await?;
+                            }
+                        }
+                        if config.is_dock(&device) {
+                            tracing::info!("Use keyboard dock: {device:?}");
+                            docks.insert(device.sysname().to_string());
+                        }
+                        if config.is_switch(&device, Switch::Lid) {
+                            tracing::info!("Use lid switch: {device:?}");
+                            lid_switches.insert(device.sysname().to_string());
+                            if lid_switches.len() == 1 && service.lid_state().await.is_none() {
+                                service.set_lid_closed(false).await?;
+                            }
+                        }
+                    }
+                    Event::Device(DeviceEvent::Removed(event)) => {
+                        let device = event.device();
+                        if switches.remove(device.sysname()) {
+                            tracing::info!("Drop input device: {device:?}");
+                            if switches.is_empty() {
+                                service.unset_tablet_mode(TabletModeSource::Switch).await?;
+                            }
+                        }
+                        if docks.remove(device.sysname()) {
+                            tracing::info!("Drop keyboard dock: {device:?}");
+                        }
+                        if lid_switches.remove(device.sysname()) {
+                            tracing::info!("Drop lid switch: {device:?}");
+                            if lid_switches.is_empty() {
+                                service.unset_lid_closed().await?;
+                            }
                         }
                     }
                     Event::Switch(SwitchEvent::Toggle(event)) => {
-                        if event.switch() == Some(Switch::TabletMode) {
-                            service
-                                .set_tablet_mode(event.switch_state() == SwitchState::On)
-                                .await?;
+                        let device = event.device();
+                        let state = event.switch_state() == SwitchState::On;
+                        match event.switch() {
+                            Some(Switch::TabletMode) if switches.contains(device.sysname()) => {
+                                service
+                                    .record(TraceEvent::Switch {
+                                        switch: TraceSwitch::TabletMode,
+                                        state,
+                                    })
+                                    .await;
+                                service
+                                    .set_tablet_mode(TabletModeSource::Switch, state)
+                                    .await?;
+                            }
+                            Some(Switch::Lid) if lid_switches.contains(device.sysname()) => {
+                                service
+                                    .record(TraceEvent::Switch {
+                                        switch: TraceSwitch::Lid,
+                                        state,
+                                    })
+                                    .await;
+                                service.set_lid_closed(state).await?;
+                            }
+                            _ => (),
                         }
                     }
                     _ => (),
                 }
             }
 
+            // devices which was used before reload may be gone or disabled
+            if !enumerated {
+                enumerated = true;
+                if switches.is_empty() {
+                    service.unset_tablet_mode(TabletModeSource::Switch).await?;
+                }
+                if lid_switches.is_empty() {
+                    service.unset_lid_closed().await?;
+                }
+                if !config.has_dock() {
+                    service.unset_tablet_mode(TabletModeSource::Dock).await?;
+                }
+            }
+
+            // detached keyboard dock means tablet mode
+            let docked = !docks.is_empty();
+            if config.has_dock() && last_docked != Some(docked) {
+                tracing::debug!("Detected keyboard dock change: {docked:?}");
+                last_docked = docked.into();
+                service
+                    .record(TraceEvent::Switch {
+                        switch: TraceSwitch::Dock,
+                        state: docked,
+                    })
+                    .await;
+                service
+                    .set_tablet_mode(TabletModeSource::Dock, !docked)
+                    .await?;
+            }
+
             input.wait().await.map_err(|error| {
                 tracing::error!("Libinput error: {error}");
                 error
@@ -169,46 +232,95 @@ impl LibinputInterface for InputInterface {
 }
 
 impl Config {
-    pub fn find_input_devices(&self) -> Result<Vec<PathBuf>> {
-        use input::{event::switch::Switch, DeviceCapability};
+    /// Find input devices with switches for diagnostic report
+    pub fn find_input_devices(&self) -> Result<Vec<InputDeviceReport>> {
+        use input::{
+            event::{switch::Switch, DeviceEvent},
+            DeviceCapability,
+        };
 
         let mut input = Input::from_udev(self.udev.iter().map(|cfg| &cfg.seat))?;
 
-        let path_prefix = Path::new("/dev/input");
-
-        let input_devices = input
-            .devices()?
-            .filter(|device| {
-                device.has_capability(DeviceCapability::Switch)
-                    && device
-                        .switch_has_switch(Switch::TabletMode)
-                        .unwrap_or(false)
-            })
-            // skip devices which disabled via config
-            .filter(|device| {
-                !self.device.iter().any(|config| {
-                    (config
-                        .name
-                        .as_ref()
-                        .map(|name| name == device.name())
-                        .unwrap_or_default()
-                        || config
-                            .vid
-                            .and_then(|vid| {
-                                config.pid.map(|pid| {
-                                    vid == device.id_vendor() && pid == device.id_product()
-                                })
-                            })
-                            .unwrap_or_default())
-                        && !config.enable
-                })
+        let devices = (&mut *input)
+            .filter_map(|event| match event {
+                Event::Device(DeviceEvent::Added(event)) => Some(event.device()),
+                _ => None,
             })
-            .map(|device| {
-                tracing::info!("Use input device: {device:?}");
-                path_prefix.join(device.sysname())
+            .filter(|device| device.has_capability(DeviceCapability::Switch))
+            .map(|device| InputDeviceReport {
+                sysname: device.sysname().into(),
+                name: device.name().into(),
+                vid: device.id_vendor(),
+                pid: device.id_product(),
+                capabilities: [
+                    (DeviceCapability::Keyboard, "keyboard"),
+                    (DeviceCapability::Pointer, "pointer"),
+                    (DeviceCapability::Touch, "touch"),
+                    (DeviceCapability::TabletTool, "tablet-tool"),
+                    (DeviceCapability::TabletPad, "tablet-pad"),
+                    (DeviceCapability::Gesture, "gesture"),
+                    (DeviceCapability::Switch, "switch"),
+                ]
+                .into_iter()
+                .filter(|(capability, _)| device.has_capability(*capability))
+                .map(|(_, name)| name.into())
+                .collect(),
+                tablet_mode_switch: device
+                    .switch_has_switch(Switch::TabletMode)
+                    .unwrap_or(false),
+                lid_switch: device.switch_has_switch(Switch::Lid).unwrap_or(false),
+                dock: self.is_dock(&device),
             })
-            .collect::<Vec<_>>();
+            .collect();
 
-        Ok(input_devices)
+        Ok(devices)
+    }
+
+    /// Whether device has switch and not disabled via config
+    pub fn is_switch(&self, device: &Device, switch: input::event::switch::Switch) -> bool {
+        use input::DeviceCapability;
+
+        device.has_capability(DeviceCapability::Switch)
+            && device.switch_has_switch(switch).unwrap_or(false)
+            // skip devices which disabled via config
+            && !self
+                .device
+                .iter()
+                .any(|config| config.matches(device) && !config.enable)
+    }
+
+    /// Whether keyboard dock configured
+    pub fn has_dock(&self) -> bool {
+        self.device
+            .iter()
+            .any(|config| config.dock && config.enable)
+    }
+
+    /// Whether device is keyboard of configured dock
+    pub fn is_dock(&self, device: &Device) -> bool {
+        use input::DeviceCapability;
+
+        device.has_capability(DeviceCapability::Keyboard)
+            && self
+                .device
+                .iter()
+                .any(|config| config.dock && config.enable && config.matches(device))
+    }
+}
+
+impl DeviceConfig {
+    /// Whether device matches by name or vendor and product ids
+    pub fn matches(&self, device: &Device) -> bool {
+        self.name
+            .as_ref()
+            .map(|name| name == device.name())
+            .unwrap_or_default()
+            || self
+                .vid
+                .and_then(|vid| {
+                    self.pid
+                        .map(|pid| vid == device.id_vendor() && pid == device.id_product())
+                })
+                .unwrap_or_default()
     }
 }
@@ -27,11 +27,116 @@ pub trait Service {
     #[dbus_proxy(property)]
     fn has_orientation(&self) -> zbus::fdo::Result<bool>;
 
-    /// Whether orientation polling is enabled
+    /// Whether device lies flat
+    #[dbus_proxy(property)]
+    fn is_flat(&self) -> zbus::fdo::Result<bool>;
+
+    /// Whether display faces up
+    #[dbus_proxy(property)]
+    fn facing_up(&self) -> zbus::fdo::Result<bool>;
+
+    /// Whether flat detection is available
+    #[dbus_proxy(property)]
+    fn has_flat(&self) -> zbus::fdo::Result<bool>;
+
+    /// Current device posture
+    #[dbus_proxy(property)]
+    fn posture(&self) -> zbus::fdo::Result<Posture>;
+
+    /// Whether posture is available
+    #[dbus_proxy(property)]
+    fn has_posture(&self) -> zbus::fdo::Result<bool>;
+
+    /// Current hinge angle in degrees
+    #[dbus_proxy(property)]
+    fn hinge_angle(&self) -> zbus::fdo::Result<f64>;
+
+    /// Whether hinge angle is available
+    #[dbus_proxy(property)]
+    fn has_hinge_angle(&self) -> zbus::fdo::Result<bool>;
+
+    /// Current ambient light level in lux
+    #[dbus_proxy(property)]
+    fn light_level(&self) -> zbus::fdo::Result<f64>;
+
+    /// Whether ambient light sensor is available
+    #[dbus_proxy(property)]
+    fn has_ambient_light(&self) -> zbus::fdo::Result<bool>;
+
+    /// Whether object is near to proximity sensor
+    #[dbus_proxy(property)]
+    fn proximity_near(&self) -> zbus::fdo::Result<bool>;
+
+    /// Whether proximity sensor is available
+    #[dbus_proxy(property)]
+    fn has_proximity(&self) -> zbus::fdo::Result<bool>;
+
+    /// Current compass heading in degrees
+    #[dbus_proxy(property)]
+    fn compass_heading(&self) -> zbus::fdo::Result<f64>;
+
+    /// Whether compass is available
     #[dbus_proxy(property)]
-    fn oritentation_poll(&self) -> zbus::fdo::Result<bool>;
+    fn has_compass(&self) -> zbus::fdo::Result<bool>;
 
-    /// Enable/disable orientation polling
+    /// Whether lid is closed
     #[dbus_proxy(property)]
-    fn set_oritentation_poll(&self, enable: bool) -> zbus::fdo::Result<()>;
+    fn lid_closed(&self) -> zbus::fdo::Result<bool>;
+
+    /// Whether lid switch is available
+    #[dbus_proxy(property)]
+    fn has_lid_switch(&self) -> zbus::fdo::Result<bool>;
+
+    /// Display accelerometer measurements
+    #[dbus_proxy(property)]
+    fn display_accel(&self) -> zbus::fdo::Result<AccelData>;
+
+    /// Whether display accelerometer measurements is available
+    #[dbus_proxy(property)]
+    fn has_display_accel(&self) -> zbus::fdo::Result<bool>;
+
+    /// Base accelerometer measurements
+    #[dbus_proxy(property)]
+    fn base_accel(&self) -> zbus::fdo::Result<AccelData>;
+
+    /// Whether base accelerometer measurements is available
+    #[dbus_proxy(property)]
+    fn has_base_accel(&self) -> zbus::fdo::Result<bool>;
+
+    /// Whether orientation polling is enabled
+    #[dbus_proxy(property)]
+    fn orientation_poll(&self) -> zbus::fdo::Result<bool>;
+
+    /// Claim orientation polling
+    fn claim_orientation(&self) -> zbus::fdo::Result<()>;
+
+    /// Release orientation polling
+    fn release_orientation(&self) -> zbus::fdo::Result<()>;
+
+    /// Claim ambient light polling
+    fn claim_light(&self) -> zbus::fdo::Result<()>;
+
+    /// Release ambient light polling
+    fn release_light(&self) -> zbus::fdo::Result<()>;
+
+    /// Claim proximity polling
+    fn claim_proximity(&self) -> zbus::fdo::Result<()>;
+
+    /// Release proximity polling
+    fn release_proximity(&self) -> zbus::fdo::Result<()>;
+
+    /// Sample accelerometers in pose and get poses which still need to be sampled
+    fn calibrate(&self, pose: CalibrationPose) -> zbus::fdo::Result<Vec<CalibrationPose>>;
+
+    /// Claim raw sensor data updates
+    fn claim_sensor_data(&self) -> zbus::fdo::Result<()>;
+
+    /// Release raw sensor data updates
+    fn release_sensor_data(&self) -> zbus::fdo::Result<()>;
+
+    /// Claim compass polling
+    fn claim_compass(&self) -> zbus::fdo::Result<()>;
+
+    /// Release compass polling
+    fn release_compass(&self) -> zbus::fdo::Result<()>;
 }
@@ -5,22 +5,34 @@ use zbus::ConnectionBuilder;
 
 mod args;
 mod config;
+mod diagnose;
 mod error;
 #[cfg(feature = "iio")]
 mod iio_iface;
 #[cfg(feature = "input")]
 mod input_iface;
+mod sensor_proxy_iface;
 mod service;
+#[cfg(feature = "iio")]
+mod simulator;
+#[cfg(any(feature = "input", feature = "iio"))]
+mod trace;
 mod types;
 
 use args::*;
 use config::*;
+use diagnose::*;
 use error::*;
 #[cfg(feature = "iio")]
 use iio_iface::*;
 #[cfg(feature = "input")]
 use input_iface::*;
+use sensor_proxy_iface::*;
 use service::*;
+#[cfg(feature = "iio")]
+use simulator::*;
+#[cfg(any(feature = "input", feature = "iio"))]
+use trace::*;
 use types::*;
 
 #[main]
@@ -64,86 +76,266 @@ async fn main() -> Result<()> {
 
     tracing::info!("Start");
 
-    #[cfg(any(feature = "libinput", feature = "iio"))]
-    let config = if let Some(path) = &args.config {
+    if args.check_config {
+        let path = args
+            .config
+            .as_ref()
+            .ok_or_else(|| Error::Config("Config file not specified".into()))?;
+        let problems = Config::check_file(path).await?;
+        if let Ok(mut config) = Config::from_file(path).await {
+            if let Some(path) = &args.calibration {
+                config.load_calibration(path).await?;
+            }
+            println!("# calibration: {}", config.calibration_origin());
+        }
+        for problem in &problems {
+            println!("{problem}");
+        }
+        return if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Config(format!("{} problems found", problems.len())))
+        };
+    }
+
+    let mut config = if let Some(path) = &args.config {
         Config::from_file(path).await?
     } else {
         Config::default()
     };
 
-    #[cfg(feature = "input")]
-    let input_devices = config.find_input_devices()?;
+    if let Some(path) = &args.calibration {
+        config.load_calibration(path).await?;
+    }
+
+    if args.print_config {
+        println!("# calibration: {}", config.calibration_origin());
+        print!("{}", toml::to_string_pretty(&config)?);
+        return Ok(());
+    }
+
+    if args.diagnose {
+        let report = Report::collect(&config).await?;
+        if args.json {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        } else {
+            print!("{}", toml::to_string_pretty(&report)?);
+        }
+        return Ok(());
+    }
+
+    #[cfg(feature = "iio")]
+    if let Some(path) = &args.replay {
+        return Iio::replay(path, &config, std::io::stdout().lock());
+    }
+
+    // iio-sensor-proxy owns sensors when used as backend
+    let use_iio = config.orientation.backend == OrientationBackend::Iio;
+
+    // no hardware backends used in simulation
+    #[cfg(feature = "iio")]
+    let simulate = args.simulate;
+    #[cfg(not(feature = "iio"))]
+    let simulate = false;
 
     #[cfg(feature = "iio")]
-    let iio_devices = config.find_iio_devices()?;
+    let mut iio_devices = if use_iio && !simulate {
+        config.find_iio_devices()?
+    } else {
+        Vec::new()
+    };
 
     if !args.dbus {
         return Ok(());
     }
 
-    let mut signals = Signals::new([Signal::Term, Signal::Quit, Signal::Int])?;
+    let mut signals = Signals::new([Signal::Term, Signal::Quit, Signal::Int, Signal::Hup])?;
 
     let service = Service::new()?;
 
+    #[cfg(any(feature = "input", feature = "iio"))]
+    if let Some(path) = &args.record {
+        service.set_recorder(Recorder::create(path)?).await;
+    }
+
     let service_name = "tablet.assist.Service";
     let service_path = "/tablet/assist";
 
     let connection = ConnectionBuilder::system()?
         .name(service_name)?
-        .serve_at(service_path, service.clone())?
-        .build()
-        .await?;
+        .serve_at(service_path, service.clone())?;
 
-    service
-        .set_interface(connection.object_server().interface(service_path).await?)
-        .await;
+    #[cfg(feature = "iio")]
+    let simulator = Simulator::new(service.clone());
 
-    let tasks = async {
-        match signals.next().await {
-            Some(Ok(sig)) => {
-                tracing::info!("Received signal {:?}", sig);
-                Ok(Some(sig))
+    #[cfg(feature = "iio")]
+    let connection = if simulate {
+        connection.serve_at(service_path, simulator.clone())?
+    } else {
+        connection
+    };
+
+    #[cfg(feature = "sensor-proxy")]
+    let connection = if use_iio {
+        connection
+            .serve_at(SENSOR_PROXY_PATH, SensorProxy::new(service.clone()))?
+            .serve_at(
+                SENSOR_PROXY_COMPASS_PATH,
+                SensorProxyCompass::new(service.clone()),
+            )?
+    } else {
+        connection
+    };
+
+    let connection = connection.build().await?;
+
+    // iio-sensor-proxy may be running already, so its name is optional
+    #[cfg(feature = "sensor-proxy")]
+    if use_iio {
+        use zbus::fdo::{RequestNameFlags, RequestNameReply};
+
+        match connection
+            .request_name_with_flags(SENSOR_PROXY_NAME, RequestNameFlags::DoNotQueue.into())
+            .await
+        {
+            Ok(RequestNameReply::PrimaryOwner | RequestNameReply::AlreadyOwner) => {
+                tracing::info!("Acquired name {SENSOR_PROXY_NAME}");
             }
-            Some(Err(error)) => {
-                tracing::error!("Signal error: {error}");
-                Err(Error::from(error))
+            Ok(reply) => {
+                tracing::warn!("Unable to acquire name {SENSOR_PROXY_NAME}: {reply:?}");
             }
-            None => {
-                tracing::error!("Signal receiver terminated");
-                Err(Error::Term)
+            Err(error) => {
+                tracing::warn!("Unable to acquire name {SENSOR_PROXY_NAME}: {error}");
             }
         }
     }
-    .boxed_local();
-
-    #[cfg(feature = "input")]
-    let tasks = if !input_devices.is_empty() {
-        // Add input task
-        tasks
-            .race(Input::process(input_devices, service.clone()))
-            .boxed_local()
-    } else {
-        tasks
-    };
 
-    #[cfg(feature = "iio")]
-    let tasks = if !iio_devices.is_empty() {
-        // Add iio task
-        tasks
-            .race(Iio::process(
-                iio_devices,
-                service.clone(),
-                &config.orientation,
-            ))
-            .boxed_local()
-    } else {
-        tasks
-    };
+    service
+        .set_interface(connection.object_server().interface(service_path).await?)
+        .await;
 
-    let res = tasks.await;
+    #[cfg(feature = "sensor-proxy")]
+    if use_iio {
+        service
+            .set_sensor_proxy_interfaces(
+                connection
+                    .object_server()
+                    .interface(SENSOR_PROXY_PATH)
+                    .await?,
+                connection
+                    .object_server()
+                    .interface(SENSOR_PROXY_COMPASS_PATH)
+                    .await?,
+            )
+            .await;
+    }
+
+    // clients tracking is kept running while backends restarted on reload
+    let mut clients = service.process_clients(connection.clone()).boxed_local();
+
+    let res = loop {
+        let tasks = async {
+            match signals.next().await {
+                Some(Ok(sig)) => {
+                    tracing::info!("Received signal {:?}", sig);
+                    Ok(Some(sig))
+                }
+                Some(Err(error)) => {
+                    tracing::error!("Signal error: {error}");
+                    Err(Error::from(error))
+                }
+                None => {
+                    tracing::error!("Signal receiver terminated");
+                    Err(Error::Term)
+                }
+            }
+        }
+        .boxed_local()
+        .race(&mut clients)
+        .boxed_local();
+
+        // Add input task, switches may appear later
+        #[cfg(feature = "input")]
+        let tasks = if !simulate {
+            tasks
+                .race(Input::process(service.clone(), &config))
+                .boxed_local()
+        } else {
+            tasks
+        };
+
+        // Add iio task, sensors may appear later
+        #[cfg(feature = "iio")]
+        let tasks = if simulate {
+            tasks.race(simulator.clone().process(&config)).boxed_local()
+        } else if use_iio {
+            tasks
+                .race(Iio::process(iio_devices.clone(), service.clone(), &config))
+                .boxed_local()
+        } else {
+            tasks
+        };
+
+        let tasks = if !use_iio && !simulate {
+            // Add iio-sensor-proxy task
+            tasks
+                .race(IioSensorProxy::process(service.clone(), connection.clone()))
+                .boxed_local()
+        } else {
+            tasks
+        };
+
+        match tasks.await {
+            Ok(Some(Signal::Hup)) => {
+                // backends restarted with new config while connection is kept
+                if let Some(path) = &args.config {
+                    let new_config = match Config::from_file(path).await {
+                        Ok(mut new_config) => match &args.calibration {
+                            Some(path) => {
+                                new_config.load_calibration(path).await.map(|_| new_config)
+                            }
+                            None => Ok(new_config),
+                        },
+                        error => error,
+                    };
+                    match new_config {
+                        Ok(mut new_config) => {
+                            tracing::info!("Reload config");
+                            if new_config.orientation.backend != config.orientation.backend {
+                                tracing::warn!("Orientation backend change requires restart");
+                                // keep running backend consistent with served interfaces
+                                new_config.orientation.backend = config.orientation.backend;
+                            }
+                            config = new_config;
+                        }
+                        Err(error) => {
+                            tracing::error!("Unable to reload config: {error}");
+                        }
+                    }
+                } else if let Some(path) = &args.calibration {
+                    if let Err(error) = config.load_calibration(path).await {
+                        tracing::error!("Unable to reload calibration: {error}");
+                    }
+                }
+                #[cfg(feature = "iio")]
+                if use_iio && !simulate {
+                    match config.find_iio_devices() {
+                        Ok(devices) => iio_devices = devices,
+                        Err(error) => {
+                            tracing::error!("Unable to find IIO devices: {error}");
+                        }
+                    }
+                }
+            }
+            res => break res,
+        }
+    };
 
     drop(connection);
 
+    #[cfg(any(feature = "input", feature = "iio"))]
+    service.flush_recorder().await;
+
     tracing::info!("Stop");
 
     match res {
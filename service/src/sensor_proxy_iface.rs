@@ -0,0 +1,153 @@
+use crate::{Claim, Orientation, Result, Service};
+use smol::{future::FutureExt, stream::StreamExt};
+use std::collections::HashSet;
+use zbus::{dbus_proxy, Connection};
+
+/// iio-sensor-proxy sensors interface
+#[dbus_proxy(
+    interface = "net.hadess.SensorProxy",
+    default_service = "net.hadess.SensorProxy",
+    default_path = "/net/hadess/SensorProxy"
+)]
+trait IioSensor {
+    /// Whether accelerometer is available
+    #[dbus_proxy(property)]
+    fn has_accelerometer(&self) -> zbus::Result<bool>;
+
+    /// Current accelerometer orientation
+    #[dbus_proxy(property)]
+    fn accelerometer_orientation(&self) -> zbus::Result<String>;
+
+    /// Claim accelerometer polling
+    fn claim_accelerometer(&self) -> zbus::Result<()>;
+
+    /// Release accelerometer polling
+    fn release_accelerometer(&self) -> zbus::Result<()>;
+}
+
+impl IioSensorProxy<'static> {
+    /// Feed orientation reported by iio-sensor-proxy to service
+    pub async fn process(
+        service: Service,
+        connection: Connection,
+    ) -> Result<Option<async_signal::Signal>> {
+        let proxy = Self::new(&connection).await?;
+        let mut owner_changes = proxy.receive_owner_changed().await?;
+        let mut available_changes = proxy.receive_has_accelerometer_changed().await;
+        let mut orientation_changes = proxy.receive_accelerometer_orientation_changed().await;
+        let mut claimed = false;
+        let mut last_orient = None;
+
+        loop {
+            let available = proxy.has_accelerometer().await.unwrap_or_default();
+            if let Err(error) = service
+                .set_sensor_available(Claim::Orientation, available)
+                .await
+            {
+                tracing::warn!("Error while setting sensor availability: {error}");
+            }
+
+            let claimed_sensors = service.claimed_sensors().await;
+            let claim = needs_claim(available, &claimed_sensors);
+            if claim != claimed {
+                let res = if claim {
+                    proxy.claim_accelerometer().await
+                } else {
+                    proxy.release_accelerometer().await
+                };
+                match res {
+                    Ok(_) => claimed = claim,
+                    Err(error) => tracing::warn!("Error while claiming accelerometer: {error}"),
+                }
+            }
+
+            // closed lid makes orientation meaningless
+            if claimed && !service.is_lid_closed().await {
+                let orient = proxy
+                    .accelerometer_orientation()
+                    .await
+                    .ok()
+                    .and_then(|orient| parse_orientation(&orient));
+
+                if let Some(orient) = orient {
+                    if last_orient != Some(orient) {
+                        tracing::debug!("Detected orientation change: {orient:?}");
+                        last_orient = orient.into();
+                        if let Err(error) = service.set_orientation(orient).await {
+                            tracing::warn!("Error while setting orientation: {error}");
+                        }
+                    }
+                }
+            }
+
+            let owner_changed = async {
+                if let Some(owner) = owner_changes.next().await {
+                    tracing::info!("iio-sensor-proxy owner changed: {owner:?}");
+                    // claims are lost when service restarted
+                    claimed = false;
+                }
+            };
+
+            owner_changed
+                .race(async {
+                    available_changes.next().await;
+                })
+                .race(async {
+                    orientation_changes.next().await;
+                })
+                .race(service.wait_claims(&claimed_sensors))
+                .await;
+        }
+    }
+}
+
+/// Whether accelerometer should be claimed from iio-sensor-proxy
+///
+/// Only orientation claims is forwarded since other sensors is not used.
+fn needs_claim(available: bool, claimed_sensors: &HashSet<Claim>) -> bool {
+    available && claimed_sensors.contains(&Claim::Orientation)
+}
+
+/// Convert iio-sensor-proxy orientation name
+fn parse_orientation(orient: &str) -> Option<Orientation> {
+    Some(match orient {
+        "normal" => Orientation::TopUp,
+        "bottom-up" => Orientation::BottomUp,
+        "left-up" => Orientation::LeftUp,
+        "right-up" => Orientation::RightUp,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn orientation_names() {
+        for (name, orient) in [
+            ("normal", Orientation::TopUp),
+            ("bottom-up", Orientation::BottomUp),
+            ("left-up", Orientation::LeftUp),
+            ("right-up", Orientation::RightUp),
+        ] {
+            assert_eq!(parse_orientation(name), Some(orient));
+        }
+        assert_eq!(parse_orientation("undefined"), None);
+        assert_eq!(parse_orientation("top-up"), None);
+    }
+
+    #[test]
+    fn claims_forwarding() {
+        let claimed = HashSet::from([Claim::Orientation]);
+        assert!(needs_claim(true, &claimed));
+        // claim is dropped when accelerometer disappears
+        assert!(!needs_claim(false, &claimed));
+        assert!(!needs_claim(true, &HashSet::new()));
+        // claims of other sensors is not forwarded
+        assert!(!needs_claim(
+            true,
+            &HashSet::from([Claim::Light, Claim::Compass, Claim::SensorData])
+        ));
+    }
+}
@@ -1,13 +1,121 @@
-use crate::{Orientation, Result};
-use smol::lock::RwLock;
-use std::sync::Arc;
-use zbus::{dbus_interface, InterfaceRef};
+use crate::{AccelData, CalibrationPose, Error, Orientation, Posture, Result};
+#[cfg(any(feature = "input", feature = "iio"))]
+use crate::{Recorder, TraceEvent};
+use event_listener::Event;
+#[cfg(feature = "iio")]
+use smol::channel::{bounded, Receiver, Sender};
+use smol::{lock::RwLock, stream::StreamExt};
+use std::{collections::HashSet, sync::Arc};
+use zbus::{
+    dbus_interface, fdo::DBusProxy, names::OwnedUniqueName, Connection, InterfaceRef, MessageHeader,
+};
+
+#[cfg(feature = "sensor-proxy")]
+mod sensor_proxy;
+
+#[cfg(feature = "sensor-proxy")]
+pub use sensor_proxy::*;
 
 /// Internal service state
 struct State {
-    tablet_mode: RwLock<Option<bool>>,
+    /// Tablet mode reported by each source
+    tablet_mode: RwLock<[Option<bool>; TabletModeSource::ALL.len()]>,
     orientation: RwLock<Option<Orientation>>,
+    posture: RwLock<Option<Posture>>,
+    hinge_angle: RwLock<Option<f64>>,
+    light_level: RwLock<Option<f64>>,
+    proximity_near: RwLock<Option<bool>>,
+    compass_heading: RwLock<Option<f64>>,
+    lid_closed: RwLock<Option<bool>>,
+    flat: RwLock<Option<bool>>,
+    facing_up: RwLock<Option<bool>>,
+    display_accel: RwLock<Option<AccelData>>,
+    base_accel: RwLock<Option<AccelData>>,
+    /// Sensors which present and can be claimed
+    sensors: RwLock<HashSet<Claim>>,
+    /// Sensors claimed by clients
+    claims: RwLock<HashSet<(Claim, OwnedUniqueName)>>,
+    /// Claims change notifier
+    claims_changed: Event,
+    /// Calibration requests
+    #[cfg(feature = "iio")]
+    calibration: (Sender<CalibrationRequest>, Receiver<CalibrationRequest>),
     interface: RwLock<Option<InterfaceRef<Service>>>,
+    /// Sensor trace writer
+    #[cfg(any(feature = "input", feature = "iio"))]
+    recorder: RwLock<Option<Recorder>>,
+    #[cfg(feature = "sensor-proxy")]
+    sensor_proxy: RwLock<Option<sensor_proxy::Interfaces>>,
+}
+
+/// Sensors which polling can be claimed by clients
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Claim {
+    Orientation,
+    Light,
+    Proximity,
+    Compass,
+    SensorData,
+}
+
+impl Claim {
+    pub const ALL: [Self; 5] = [
+        Self::Orientation,
+        Self::Light,
+        Self::Proximity,
+        Self::Compass,
+        Self::SensorData,
+    ];
+}
+
+/// Source of tablet mode state
+///
+/// Sources is ordered by precedence, so hinge angle estimation is used
+/// only when no input device reports tablet mode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TabletModeSource {
+    /// Tablet-mode switches
+    Switch,
+    /// Keyboard docks, tablet mode when detached
+    Dock,
+    /// Estimation using hinge angle
+    Hinge,
+}
+
+impl TabletModeSource {
+    pub const ALL: [Self; 3] = [Self::Switch, Self::Dock, Self::Hinge];
+}
+
+/// Tablet mode of source which takes precedence
+fn current_tablet_mode(modes: &[Option<bool>]) -> Option<bool> {
+    modes.iter().find_map(|mode| *mode)
+}
+
+/// Sensor values published by service
+///
+/// Sensors task restarted on reload continues from published values,
+/// so unchanged ones is not reported again and dropped ones is unset.
+#[cfg(feature = "iio")]
+pub struct SensorState {
+    pub orientation: Option<Orientation>,
+    /// Flat and facing up
+    pub flat: Option<(bool, bool)>,
+    /// Hinge angle in degrees
+    pub hinge_angle: Option<f64>,
+    pub posture: Option<Posture>,
+    /// Tablet mode estimated using hinge angle
+    pub tablet_mode: Option<bool>,
+    pub light_level: Option<f64>,
+    pub proximity_near: Option<bool>,
+    pub compass_heading: Option<f64>,
+}
+
+/// Request to sample accelerometers in pose
+#[cfg(feature = "iio")]
+pub struct CalibrationRequest {
+    pub pose: CalibrationPose,
+    /// Poses which still need to be sampled
+    pub reply: Sender<Result<Vec<CalibrationPose>>>,
 }
 
 #[derive(Clone)]
@@ -21,13 +129,13 @@ impl Service {
     /// Current tablet-mode state property
     #[dbus_interface(property)]
     async fn tablet_mode(&self) -> bool {
-        self.state.tablet_mode.read().await.unwrap_or_default()
+        self.current_tablet_mode().await.unwrap_or_default()
     }
 
     /// Tablet-mode available property
     #[dbus_interface(property)]
     async fn has_tablet_mode(&self) -> bool {
-        self.state.tablet_mode.read().await.is_some()
+        self.current_tablet_mode().await.is_some()
     }
 
     /// Current screen orientation property
@@ -39,7 +147,262 @@ impl Service {
     /// Orientation available property
     #[dbus_interface(property)]
     async fn has_orientation(&self) -> bool {
-        self.state.orientation.read().await.is_some()
+        self.has_sensor(Claim::Orientation).await
+    }
+
+    /// Whether device lies flat property
+    #[dbus_interface(property)]
+    async fn is_flat(&self) -> bool {
+        self.state.flat.read().await.unwrap_or_default()
+    }
+
+    /// Whether display faces up property
+    #[dbus_interface(property)]
+    async fn facing_up(&self) -> bool {
+        self.state.facing_up.read().await.unwrap_or_default()
+    }
+
+    /// Flat detection available property
+    #[dbus_interface(property)]
+    async fn has_flat(&self) -> bool {
+        self.state.flat.read().await.is_some()
+    }
+
+    /// Current device posture property
+    #[dbus_interface(property)]
+    async fn posture(&self) -> Posture {
+        let posture = *self.state.posture.read().await;
+        let tablet_mode = self.current_tablet_mode().await;
+        combine_posture(posture, tablet_mode)
+    }
+
+    /// Posture available property
+    #[dbus_interface(property)]
+    async fn has_posture(&self) -> bool {
+        self.state.posture.read().await.is_some() || self.current_tablet_mode().await.is_some()
+    }
+
+    /// Current hinge angle in degrees property
+    #[dbus_interface(property)]
+    async fn hinge_angle(&self) -> f64 {
+        self.state.hinge_angle.read().await.unwrap_or_default()
+    }
+
+    /// Hinge angle available property
+    #[dbus_interface(property)]
+    async fn has_hinge_angle(&self) -> bool {
+        self.state.hinge_angle.read().await.is_some()
+    }
+
+    /// Current ambient light level in lux property
+    #[dbus_interface(property)]
+    async fn light_level(&self) -> f64 {
+        self.state.light_level.read().await.unwrap_or_default()
+    }
+
+    /// Ambient light available property
+    #[dbus_interface(property)]
+    async fn has_ambient_light(&self) -> bool {
+        self.has_sensor(Claim::Light).await
+    }
+
+    /// Whether object is near to proximity sensor property
+    #[dbus_interface(property)]
+    async fn proximity_near(&self) -> bool {
+        self.state.proximity_near.read().await.unwrap_or_default()
+    }
+
+    /// Proximity sensor available property
+    #[dbus_interface(property)]
+    async fn has_proximity(&self) -> bool {
+        self.has_sensor(Claim::Proximity).await
+    }
+
+    /// Current compass heading in degrees property
+    #[dbus_interface(property)]
+    async fn compass_heading(&self) -> f64 {
+        self.state.compass_heading.read().await.unwrap_or_default()
+    }
+
+    /// Compass available property
+    #[dbus_interface(property)]
+    async fn has_compass(&self) -> bool {
+        self.has_sensor(Claim::Compass).await
+    }
+
+    /// Current lid state property
+    #[dbus_interface(property)]
+    async fn lid_closed(&self) -> bool {
+        self.state.lid_closed.read().await.unwrap_or_default()
+    }
+
+    /// Lid switch available property
+    #[dbus_interface(property)]
+    async fn has_lid_switch(&self) -> bool {
+        self.state.lid_closed.read().await.is_some()
+    }
+
+    /// Display accelerometer measurements property
+    #[dbus_interface(property)]
+    async fn display_accel(&self) -> AccelData {
+        self.state.display_accel.read().await.unwrap_or_default()
+    }
+
+    /// Display accelerometer measurements available property
+    #[dbus_interface(property)]
+    async fn has_display_accel(&self) -> bool {
+        self.state.display_accel.read().await.is_some()
+    }
+
+    /// Base accelerometer measurements property
+    #[dbus_interface(property)]
+    async fn base_accel(&self) -> AccelData {
+        self.state.base_accel.read().await.unwrap_or_default()
+    }
+
+    /// Base accelerometer measurements available property
+    #[dbus_interface(property)]
+    async fn has_base_accel(&self) -> bool {
+        self.state.base_accel.read().await.is_some()
+    }
+
+    /// Orientation polling enabled property
+    #[dbus_interface(property)]
+    async fn orientation_poll(&self) -> bool {
+        self.claimed(Claim::Orientation).await
+    }
+
+    /// Claim orientation polling by client
+    async fn claim_orientation(&self, #[zbus(header)] header: MessageHeader<'_>) -> Result<()> {
+        if let Some(sender) = header.sender()? {
+            self.add_claim(Claim::Orientation, sender.to_owned().into())
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Release orientation polling by client
+    async fn release_orientation(&self, #[zbus(header)] header: MessageHeader<'_>) -> Result<()> {
+        if let Some(sender) = header.sender()? {
+            self.remove_claim(Claim::Orientation, sender.to_owned().into())
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Claim ambient light polling by client
+    async fn claim_light(&self, #[zbus(header)] header: MessageHeader<'_>) -> Result<()> {
+        if let Some(sender) = header.sender()? {
+            self.add_claim(Claim::Light, sender.to_owned().into())
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Release ambient light polling by client
+    async fn release_light(&self, #[zbus(header)] header: MessageHeader<'_>) -> Result<()> {
+        if let Some(sender) = header.sender()? {
+            self.remove_claim(Claim::Light, sender.to_owned().into())
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Claim proximity polling by client
+    async fn claim_proximity(&self, #[zbus(header)] header: MessageHeader<'_>) -> Result<()> {
+        if let Some(sender) = header.sender()? {
+            self.add_claim(Claim::Proximity, sender.to_owned().into())
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Release proximity polling by client
+    async fn release_proximity(&self, #[zbus(header)] header: MessageHeader<'_>) -> Result<()> {
+        if let Some(sender) = header.sender()? {
+            self.remove_claim(Claim::Proximity, sender.to_owned().into())
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Sample accelerometers in pose and return poses which still need to be sampled
+    ///
+    /// When all poses sampled the corrections are applied and stored in config.
+    async fn calibrate(&self, pose: CalibrationPose) -> Result<Vec<CalibrationPose>> {
+        if !self.has_sensor(Claim::SensorData).await {
+            return Err(Error::Calibration("No accelerometers available".into()));
+        }
+
+        #[cfg(feature = "iio")]
+        {
+            let (reply, response) = bounded(1);
+            self.state
+                .calibration
+                .0
+                .send(CalibrationRequest { pose, reply })
+                .await
+                .map_err(|_| Error::Term)?;
+
+            response.recv().await.map_err(|_| Error::Term)?
+        }
+
+        // accelerometers is only provided by IIO backend
+        #[cfg(not(feature = "iio"))]
+        {
+            let _ = pose;
+            Err(Error::Calibration("No accelerometers available".into()))
+        }
+    }
+
+    /// Claim raw sensor data updates by client
+    async fn claim_sensor_data(&self, #[zbus(header)] header: MessageHeader<'_>) -> Result<()> {
+        if let Some(sender) = header.sender()? {
+            self.add_claim(Claim::SensorData, sender.to_owned().into())
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Release raw sensor data updates by client
+    async fn release_sensor_data(&self, #[zbus(header)] header: MessageHeader<'_>) -> Result<()> {
+        if let Some(sender) = header.sender()? {
+            self.remove_claim(Claim::SensorData, sender.to_owned().into())
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Claim compass polling by client
+    async fn claim_compass(&self, #[zbus(header)] header: MessageHeader<'_>) -> Result<()> {
+        if let Some(sender) = header.sender()? {
+            self.add_claim(Claim::Compass, sender.to_owned().into())
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Release compass polling by client
+    async fn release_compass(&self, #[zbus(header)] header: MessageHeader<'_>) -> Result<()> {
+        if let Some(sender) = header.sender()? {
+            self.remove_claim(Claim::Compass, sender.to_owned().into())
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+/// Refine posture detected using accelerometers with tablet-mode switch state
+fn combine_posture(posture: Option<Posture>, tablet_mode: Option<bool>) -> Posture {
+    match (posture, tablet_mode) {
+        (Some(Posture::Closed), _) => Posture::Closed,
+        // switch reports laptop mode
+        (Some(_), Some(false)) => Posture::Laptop,
+        // switch reports tablet mode but sensors cannot tell more
+        (Some(Posture::Laptop), Some(true)) => Posture::Tablet,
+        (Some(posture), _) => posture,
+        (None, Some(true)) => Posture::Tablet,
+        (None, _) => Posture::Laptop,
     }
 }
 
@@ -47,9 +410,28 @@ impl Service {
     pub fn new() -> Result<Self> {
         Ok(Service {
             state: Arc::new(State {
-                tablet_mode: RwLock::new(None),
+                tablet_mode: RwLock::new(Default::default()),
                 orientation: RwLock::new(None),
+                posture: RwLock::new(None),
+                hinge_angle: RwLock::new(None),
+                light_level: RwLock::new(None),
+                proximity_near: RwLock::new(None),
+                compass_heading: RwLock::new(None),
+                lid_closed: RwLock::new(None),
+                flat: RwLock::new(None),
+                facing_up: RwLock::new(None),
+                display_accel: RwLock::new(None),
+                base_accel: RwLock::new(None),
+                sensors: RwLock::new(HashSet::new()),
+                claims: RwLock::new(HashSet::new()),
+                claims_changed: Event::new(),
+                #[cfg(feature = "iio")]
+                calibration: bounded(1),
                 interface: RwLock::new(None),
+                #[cfg(any(feature = "input", feature = "iio"))]
+                recorder: RwLock::new(None),
+                #[cfg(feature = "sensor-proxy")]
+                sensor_proxy: RwLock::new(None),
             }),
         })
     }
@@ -58,41 +440,651 @@ impl Service {
         *self.state.interface.write().await = Some(interface);
     }
 
-    pub async fn set_tablet_mode(&self, mode: bool) -> Result<()> {
+    #[cfg(any(feature = "input", feature = "iio"))]
+    pub async fn set_recorder(&self, recorder: Recorder) {
+        *self.state.recorder.write().await = Some(recorder);
+    }
+
+    #[cfg(feature = "iio")]
+    pub async fn recorder(&self) -> Option<Recorder> {
+        self.state.recorder.read().await.clone()
+    }
+
+    /// Write buffered sensor trace events
+    #[cfg(any(feature = "input", feature = "iio"))]
+    pub async fn flush_recorder(&self) {
+        if let Some(recorder) = &*self.state.recorder.read().await {
+            if let Err(error) = recorder.flush() {
+                tracing::warn!("Error while recording trace: {error}");
+            }
+        }
+    }
+
+    /// Append event to sensor trace when recording
+    #[cfg(any(feature = "input", feature = "iio"))]
+    pub async fn record(&self, event: TraceEvent) {
+        if let Some(recorder) = &*self.state.recorder.read().await {
+            if let Err(error) = recorder.record(&event) {
+                tracing::warn!("Error while recording trace: {error}");
+            }
+        }
+    }
+
+    /// Whether lid is known to be closed
+    pub async fn is_lid_closed(&self) -> bool {
+        self.state.lid_closed.read().await.unwrap_or_default()
+    }
+
+    /// Lid state reported by switch
+    #[cfg(feature = "input")]
+    pub async fn lid_state(&self) -> Option<bool> {
+        *self.state.lid_closed.read().await
+    }
+
+    /// Tablet mode reported by source
+    #[cfg(feature = "input")]
+    pub async fn source_tablet_mode(&self, source: TabletModeSource) -> Option<bool> {
+        self.state.tablet_mode.read().await[source as usize]
+    }
+
+    /// Sensor values currently published
+    #[cfg(feature = "iio")]
+    pub async fn sensor_state(&self) -> SensorState {
+        let flat = *self.state.flat.read().await;
+        let facing_up = *self.state.facing_up.read().await;
+
+        SensorState {
+            orientation: *self.state.orientation.read().await,
+            flat: flat.zip(facing_up),
+            hinge_angle: *self.state.hinge_angle.read().await,
+            posture: *self.state.posture.read().await,
+            tablet_mode: self.state.tablet_mode.read().await[TabletModeSource::Hinge as usize],
+            light_level: *self.state.light_level.read().await,
+            proximity_near: *self.state.proximity_near.read().await,
+            compass_heading: *self.state.compass_heading.read().await,
+        }
+    }
+
+    /// Whether sensor is present even when it isn't polled
+    async fn has_sensor(&self, claim: Claim) -> bool {
+        self.state.sensors.read().await.contains(&claim)
+    }
+
+    /// Wait for calibration request
+    #[cfg(feature = "iio")]
+    pub async fn wait_calibration(&self) -> Option<CalibrationRequest> {
+        self.state.calibration.1.recv().await.ok()
+    }
+
+    /// Mark sensor as present or not
+    pub async fn set_sensor_available(&self, claim: Claim, available: bool) -> Result<()> {
+        let changed = {
+            let mut sensors = self.state.sensors.write().await;
+            if available {
+                sensors.insert(claim)
+            } else {
+                sensors.remove(&claim)
+            }
+        };
+
+        if changed {
+            tracing::debug!("Sensor {claim:?} available: {available}");
+
+            let iface = self.state.interface.read().await;
+            let sigctx = iface.as_ref().unwrap().signal_context();
+
+            match claim {
+                Claim::Light => self.has_ambient_light_changed(sigctx).await?,
+                Claim::Proximity => self.has_proximity_changed(sigctx).await?,
+                Claim::Compass => self.has_compass_changed(sigctx).await?,
+                Claim::Orientation => self.has_orientation_changed(sigctx).await?,
+                Claim::SensorData => (),
+            }
+
+            #[cfg(feature = "sensor-proxy")]
+            self.sensor_proxy_changed(claim, true).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Whether any client claimed sensor
+    pub async fn claimed(&self, claim: Claim) -> bool {
+        self.state
+            .claims
+            .read()
+            .await
+            .iter()
+            .any(|(had_claim, _)| *had_claim == claim)
+    }
+
+    /// Sensors claimed by any client
+    pub async fn claimed_sensors(&self) -> HashSet<Claim> {
+        self.state
+            .claims
+            .read()
+            .await
+            .iter()
+            .map(|(claim, _)| *claim)
+            .collect()
+    }
+
+    /// Wait until claimed sensors differs from given
+    pub async fn wait_claims(&self, claimed: &HashSet<Claim>) {
+        loop {
+            // listen before check to not miss notification
+            let listener = self.state.claims_changed.listen();
+            if self.claimed_sensors().await != *claimed {
+                return;
+            }
+            listener.await;
+        }
+    }
+
+    async fn add_claim(&self, claim: Claim, name: OwnedUniqueName) -> Result<()> {
+        tracing::debug!("Claim {claim:?} by {name}");
+        let changed = {
+            let mut claims = self.state.claims.write().await;
+            let changed = !claims.iter().any(|(had_claim, _)| *had_claim == claim);
+            claims.insert((claim, name));
+            changed
+        };
+        if changed {
+            self.notify_claim(claim).await?;
+        }
+        Ok(())
+    }
+
+    async fn remove_claim(&self, claim: Claim, name: OwnedUniqueName) -> Result<()> {
+        let changed = {
+            let mut claims = self.state.claims.write().await;
+            if !claims.remove(&(claim, name.clone())) {
+                return Ok(());
+            }
+            !claims.iter().any(|(had_claim, _)| *had_claim == claim)
+        };
+        tracing::debug!("Release {claim:?} by {name}");
+        if changed {
+            self.notify_claim(claim).await?;
+        }
+        Ok(())
+    }
+
+    async fn notify_claim(&self, claim: Claim) -> Result<()> {
+        self.state.claims_changed.notify(usize::MAX);
+
+        if claim == Claim::Orientation {
+            // interface is missing until served
+            if let Some(iface) = self.state.interface.read().await.as_ref() {
+                self.orientation_poll_changed(iface.signal_context())
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Drop claims of clients which disconnected from bus
+    pub async fn process_clients(
+        &self,
+        connection: Connection,
+    ) -> Result<Option<async_signal::Signal>> {
+        use zbus::names::BusName;
+
+        let dbus = DBusProxy::new(&connection).await?;
+        let mut changes = dbus.receive_name_owner_changed().await?;
+
+        while let Some(change) = changes.next().await {
+            let args = change.args()?;
+            if args.new_owner().is_none() {
+                if let BusName::Unique(name) = args.name() {
+                    let name: OwnedUniqueName = name.to_owned().into();
+                    for claim in Claim::ALL {
+                        self.remove_claim(claim, name.clone()).await?;
+                    }
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Tablet mode of source which takes precedence
+    async fn current_tablet_mode(&self) -> Option<bool> {
+        current_tablet_mode(&*self.state.tablet_mode.read().await)
+    }
+
+    #[cfg(any(feature = "input", feature = "iio"))]
+    pub async fn set_tablet_mode(&self, source: TabletModeSource, mode: bool) -> Result<()> {
+        self.update_tablet_mode(source, Some(mode)).await
+    }
+
+    #[cfg(any(feature = "input", feature = "iio"))]
+    pub async fn unset_tablet_mode(&self, source: TabletModeSource) -> Result<()> {
+        self.update_tablet_mode(source, None).await
+    }
+
+    #[cfg(any(feature = "input", feature = "iio"))]
+    async fn update_tablet_mode(&self, source: TabletModeSource, mode: Option<bool>) -> Result<()> {
+        let (had, has) = {
+            let mut modes = self.state.tablet_mode.write().await;
+            let had = current_tablet_mode(&*modes);
+            modes[source as usize] = mode;
+            (had, current_tablet_mode(&*modes))
+        };
+
+        if had == has {
+            return Ok(());
+        }
+
+        let iface = self.state.interface.read().await;
+        let sigctx = iface.as_ref().unwrap().signal_context();
+
+        self.tablet_mode_changed(sigctx).await?;
+        self.posture_changed(sigctx).await?;
+        if had.is_some() != has.is_some() {
+            self.has_tablet_mode_changed(sigctx).await?;
+            self.has_posture_changed(sigctx).await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn set_orientation(&self, orientation: Orientation) -> Result<()> {
+        *self.state.orientation.write().await = Some(orientation);
+
+        let iface = self.state.interface.read().await;
+        let sigctx = iface.as_ref().unwrap().signal_context();
+
+        self.orientation_changed(sigctx).await?;
+
+        #[cfg(feature = "sensor-proxy")]
+        self.sensor_proxy_changed(Claim::Orientation, false).await?;
+
+        Ok(())
+    }
+
+    #[cfg(feature = "iio")]
+    pub async fn unset_orientation(&self) -> Result<()> {
+        let avail = self.state.orientation.write().await.take().is_some();
+
+        if avail {
+            let iface = self.state.interface.read().await;
+            let sigctx = iface.as_ref().unwrap().signal_context();
+
+            self.orientation_changed(sigctx).await?;
+        }
+
+        #[cfg(feature = "sensor-proxy")]
+        self.sensor_proxy_changed(Claim::Orientation, false).await?;
+
+        Ok(())
+    }
+
+    #[cfg(feature = "iio")]
+    pub async fn set_flat(&self, flat: bool, facing_up: bool) -> Result<()> {
         let avail = {
-            let mut val = self.state.tablet_mode.write().await;
+            let mut val = self.state.flat.write().await;
             let avail = val.is_some();
-            *val = Some(mode);
+            *val = Some(flat);
+            *self.state.facing_up.write().await = Some(facing_up);
             avail
         };
 
         let iface = self.state.interface.read().await;
         let sigctx = iface.as_ref().unwrap().signal_context();
 
-        self.tablet_mode_changed(sigctx).await?;
+        self.is_flat_changed(sigctx).await?;
+        self.facing_up_changed(sigctx).await?;
         if !avail {
-            self.has_tablet_mode_changed(sigctx).await?;
+            self.has_flat_changed(sigctx).await?;
         }
 
         Ok(())
     }
 
-    pub async fn set_orientation(&self, orientation: Orientation) -> Result<()> {
+    #[cfg(feature = "iio")]
+    pub async fn unset_flat(&self) -> Result<()> {
+        let avail = self.state.flat.write().await.take().is_some();
+        *self.state.facing_up.write().await = None;
+
+        if avail {
+            let iface = self.state.interface.read().await;
+            let sigctx = iface.as_ref().unwrap().signal_context();
+
+            self.is_flat_changed(sigctx).await?;
+            self.facing_up_changed(sigctx).await?;
+            self.has_flat_changed(sigctx).await?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "iio")]
+    pub async fn set_posture(&self, posture: Posture) -> Result<()> {
         let avail = {
-            let mut val = self.state.orientation.write().await;
+            let mut val = self.state.posture.write().await;
             let avail = val.is_some();
-            *val = Some(orientation);
+            *val = Some(posture);
             avail
         };
 
         let iface = self.state.interface.read().await;
         let sigctx = iface.as_ref().unwrap().signal_context();
 
-        self.orientation_changed(sigctx).await?;
+        self.posture_changed(sigctx).await?;
+        if !avail {
+            self.has_posture_changed(sigctx).await?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "iio")]
+    pub async fn unset_posture(&self) -> Result<()> {
+        let avail = self.state.posture.write().await.take().is_some();
+
+        if avail {
+            let iface = self.state.interface.read().await;
+            let sigctx = iface.as_ref().unwrap().signal_context();
+
+            self.posture_changed(sigctx).await?;
+            self.has_posture_changed(sigctx).await?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "iio")]
+    pub async fn set_hinge_angle(&self, angle: f64) -> Result<()> {
+        let avail = {
+            let mut val = self.state.hinge_angle.write().await;
+            let avail = val.is_some();
+            *val = Some(angle);
+            avail
+        };
+
+        let iface = self.state.interface.read().await;
+        let sigctx = iface.as_ref().unwrap().signal_context();
+
+        self.hinge_angle_changed(sigctx).await?;
         if !avail {
-            self.has_orientation_changed(sigctx).await?;
+            self.has_hinge_angle_changed(sigctx).await?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "iio")]
+    pub async fn unset_hinge_angle(&self) -> Result<()> {
+        let avail = self.state.hinge_angle.write().await.take().is_some();
+
+        if avail {
+            let iface = self.state.interface.read().await;
+            let sigctx = iface.as_ref().unwrap().signal_context();
+
+            self.hinge_angle_changed(sigctx).await?;
+            self.has_hinge_angle_changed(sigctx).await?;
         }
 
         Ok(())
     }
+
+    #[cfg(feature = "iio")]
+    pub async fn set_light_level(&self, level: f64) -> Result<()> {
+        *self.state.light_level.write().await = Some(level);
+
+        let iface = self.state.interface.read().await;
+        let sigctx = iface.as_ref().unwrap().signal_context();
+
+        self.light_level_changed(sigctx).await?;
+
+        #[cfg(feature = "sensor-proxy")]
+        self.sensor_proxy_changed(Claim::Light, false).await?;
+
+        Ok(())
+    }
+
+    #[cfg(feature = "iio")]
+    pub async fn unset_light_level(&self) -> Result<()> {
+        let avail = self.state.light_level.write().await.take().is_some();
+
+        if avail {
+            let iface = self.state.interface.read().await;
+            let sigctx = iface.as_ref().unwrap().signal_context();
+
+            self.light_level_changed(sigctx).await?;
+
+            #[cfg(feature = "sensor-proxy")]
+            self.sensor_proxy_changed(Claim::Light, false).await?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "iio")]
+    pub async fn set_proximity_near(&self, near: bool) -> Result<()> {
+        *self.state.proximity_near.write().await = Some(near);
+
+        let iface = self.state.interface.read().await;
+        let sigctx = iface.as_ref().unwrap().signal_context();
+
+        self.proximity_near_changed(sigctx).await?;
+
+        #[cfg(feature = "sensor-proxy")]
+        self.sensor_proxy_changed(Claim::Proximity, false).await?;
+
+        Ok(())
+    }
+
+    #[cfg(feature = "iio")]
+    pub async fn unset_proximity_near(&self) -> Result<()> {
+        let avail = self.state.proximity_near.write().await.take().is_some();
+
+        if avail {
+            let iface = self.state.interface.read().await;
+            let sigctx = iface.as_ref().unwrap().signal_context();
+
+            self.proximity_near_changed(sigctx).await?;
+
+            #[cfg(feature = "sensor-proxy")]
+            self.sensor_proxy_changed(Claim::Proximity, false).await?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "iio")]
+    pub async fn set_compass_heading(&self, heading: f64) -> Result<()> {
+        *self.state.compass_heading.write().await = Some(heading);
+
+        let iface = self.state.interface.read().await;
+        let sigctx = iface.as_ref().unwrap().signal_context();
+
+        self.compass_heading_changed(sigctx).await?;
+
+        #[cfg(feature = "sensor-proxy")]
+        self.sensor_proxy_changed(Claim::Compass, false).await?;
+
+        Ok(())
+    }
+
+    #[cfg(feature = "iio")]
+    pub async fn unset_compass_heading(&self) -> Result<()> {
+        let avail = self.state.compass_heading.write().await.take().is_some();
+
+        if avail {
+            let iface = self.state.interface.read().await;
+            let sigctx = iface.as_ref().unwrap().signal_context();
+
+            self.compass_heading_changed(sigctx).await?;
+
+            #[cfg(feature = "sensor-proxy")]
+            self.sensor_proxy_changed(Claim::Compass, false).await?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(any(feature = "input", feature = "iio"))]
+    pub async fn set_lid_closed(&self, closed: bool) -> Result<()> {
+        let avail = {
+            let mut val = self.state.lid_closed.write().await;
+            let avail = val.is_some();
+            *val = Some(closed);
+            avail
+        };
+
+        let iface = self.state.interface.read().await;
+        let sigctx = iface.as_ref().unwrap().signal_context();
+
+        self.lid_closed_changed(sigctx).await?;
+        if !avail {
+            self.has_lid_switch_changed(sigctx).await?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "input")]
+    pub async fn unset_lid_closed(&self) -> Result<()> {
+        let avail = self.state.lid_closed.write().await.take().is_some();
+
+        if avail {
+            let iface = self.state.interface.read().await;
+            let sigctx = iface.as_ref().unwrap().signal_context();
+
+            self.lid_closed_changed(sigctx).await?;
+            self.has_lid_switch_changed(sigctx).await?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "iio")]
+    pub async fn set_display_accel(&self, data: AccelData) -> Result<()> {
+        let avail = {
+            let mut val = self.state.display_accel.write().await;
+            let avail = val.is_some();
+            *val = Some(data);
+            avail
+        };
+
+        let iface = self.state.interface.read().await;
+        let sigctx = iface.as_ref().unwrap().signal_context();
+
+        self.display_accel_changed(sigctx).await?;
+        if !avail {
+            self.has_display_accel_changed(sigctx).await?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "iio")]
+    pub async fn unset_display_accel(&self) -> Result<()> {
+        let avail = self.state.display_accel.write().await.take().is_some();
+
+        if avail {
+            let iface = self.state.interface.read().await;
+            let sigctx = iface.as_ref().unwrap().signal_context();
+
+            self.display_accel_changed(sigctx).await?;
+            self.has_display_accel_changed(sigctx).await?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "iio")]
+    pub async fn set_base_accel(&self, data: AccelData) -> Result<()> {
+        let avail = {
+            let mut val = self.state.base_accel.write().await;
+            let avail = val.is_some();
+            *val = Some(data);
+            avail
+        };
+
+        let iface = self.state.interface.read().await;
+        let sigctx = iface.as_ref().unwrap().signal_context();
+
+        self.base_accel_changed(sigctx).await?;
+        if !avail {
+            self.has_base_accel_changed(sigctx).await?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "iio")]
+    pub async fn unset_base_accel(&self) -> Result<()> {
+        let avail = self.state.base_accel.write().await.take().is_some();
+
+        if avail {
+            let iface = self.state.interface.read().await;
+            let sigctx = iface.as_ref().unwrap().signal_context();
+
+            self.base_accel_changed(sigctx).await?;
+            self.has_base_accel_changed(sigctx).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tablet_mode_precedence() {
+        let modes = |modes: &[(TabletModeSource, bool)]| {
+            let mut all = [None; TabletModeSource::ALL.len()];
+            for (source, mode) in modes {
+                all[*source as usize] = Some(*mode);
+            }
+            current_tablet_mode(&all)
+        };
+
+        assert_eq!(modes(&[]), None);
+        assert_eq!(modes(&[(TabletModeSource::Hinge, true)]), Some(true));
+        // input devices overrides hinge angle estimation
+        assert_eq!(
+            modes(&[
+                (TabletModeSource::Dock, false),
+                (TabletModeSource::Hinge, true)
+            ]),
+            Some(false)
+        );
+        // switch overrides keyboard dock
+        assert_eq!(
+            modes(&[
+                (TabletModeSource::Switch, true),
+                (TabletModeSource::Dock, false),
+                (TabletModeSource::Hinge, false)
+            ]),
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn posture_with_tablet_mode() {
+        assert_eq!(combine_posture(None, None), Posture::Laptop);
+        assert_eq!(combine_posture(None, Some(true)), Posture::Tablet);
+        assert_eq!(combine_posture(Some(Posture::Tent), None), Posture::Tent);
+        // switch reports laptop mode
+        assert_eq!(
+            combine_posture(Some(Posture::Stand), Some(false)),
+            Posture::Laptop
+        );
+        // switch reports tablet mode while hinge angle does not
+        assert_eq!(
+            combine_posture(Some(Posture::Laptop), Some(true)),
+            Posture::Tablet
+        );
+        assert_eq!(
+            combine_posture(Some(Posture::Closed), Some(true)),
+            Posture::Closed
+        );
+    }
 }
@@ -0,0 +1,347 @@
+use super::{Claim, Service};
+use crate::{Orientation, Result};
+use zbus::{dbus_interface, InterfaceRef, MessageHeader};
+
+/// Bus name of iio-sensor-proxy compatible service
+pub const SENSOR_PROXY_NAME: &str = "net.hadess.SensorProxy";
+
+/// Object path of iio-sensor-proxy compatible service
+pub const SENSOR_PROXY_PATH: &str = "/net/hadess/SensorProxy";
+
+/// Object path of iio-sensor-proxy compatible compass
+pub const SENSOR_PROXY_COMPASS_PATH: &str = "/net/hadess/SensorProxy/Compass";
+
+/// Interfaces which is used to emit signals
+pub(super) struct Interfaces {
+    sensor_proxy: InterfaceRef<SensorProxy>,
+    compass: InterfaceRef<SensorProxyCompass>,
+}
+
+/// iio-sensor-proxy compatible sensors interface
+#[derive(Clone)]
+pub struct SensorProxy {
+    service: Service,
+}
+
+impl SensorProxy {
+    pub fn new(service: Service) -> Self {
+        Self { service }
+    }
+}
+
+#[dbus_interface(name = "net.hadess.SensorProxy")]
+impl SensorProxy {
+    /// Accelerometer available property
+    #[dbus_interface(property)]
+    async fn has_accelerometer(&self) -> bool {
+        self.service.has_sensor(Claim::Orientation).await
+    }
+
+    /// Current screen orientation property
+    #[dbus_interface(property)]
+    async fn accelerometer_orientation(&self) -> &str {
+        match *self.service.state.orientation.read().await {
+            Some(Orientation::TopUp) => "normal",
+            Some(Orientation::BottomUp) => "bottom-up",
+            Some(Orientation::LeftUp) => "left-up",
+            Some(Orientation::RightUp) => "right-up",
+            None => "undefined",
+        }
+    }
+
+    /// Ambient light sensor available property
+    #[dbus_interface(property)]
+    async fn has_ambient_light(&self) -> bool {
+        self.service.has_sensor(Claim::Light).await
+    }
+
+    /// Light level unit property
+    #[dbus_interface(property)]
+    async fn light_level_unit(&self) -> &str {
+        "lux"
+    }
+
+    /// Current ambient light level property
+    #[dbus_interface(property)]
+    async fn light_level(&self) -> f64 {
+        self.service
+            .state
+            .light_level
+            .read()
+            .await
+            .unwrap_or_default()
+    }
+
+    /// Proximity sensor available property
+    #[dbus_interface(property)]
+    async fn has_proximity(&self) -> bool {
+        self.service.has_sensor(Claim::Proximity).await
+    }
+
+    /// Whether object is near to proximity sensor property
+    #[dbus_interface(property)]
+    async fn proximity_near(&self) -> bool {
+        self.service
+            .state
+            .proximity_near
+            .read()
+            .await
+            .unwrap_or_default()
+    }
+
+    /// Claim accelerometer polling by client
+    async fn claim_accelerometer(&self, #[zbus(header)] header: MessageHeader<'_>) -> Result<()> {
+        if let Some(sender) = header.sender()? {
+            self.service
+                .add_claim(Claim::Orientation, sender.to_owned().into())
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Release accelerometer polling by client
+    async fn release_accelerometer(&self, #[zbus(header)] header: MessageHeader<'_>) -> Result<()> {
+        if let Some(sender) = header.sender()? {
+            self.service
+                .remove_claim(Claim::Orientation, sender.to_owned().into())
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Claim ambient light sensor polling by client
+    async fn claim_light(&self, #[zbus(header)] header: MessageHeader<'_>) -> Result<()> {
+        if let Some(sender) = header.sender()? {
+            self.service
+                .add_claim(Claim::Light, sender.to_owned().into())
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Release ambient light sensor polling by client
+    async fn release_light(&self, #[zbus(header)] header: MessageHeader<'_>) -> Result<()> {
+        if let Some(sender) = header.sender()? {
+            self.service
+                .remove_claim(Claim::Light, sender.to_owned().into())
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Claim proximity sensor polling by client
+    async fn claim_proximity(&self, #[zbus(header)] header: MessageHeader<'_>) -> Result<()> {
+        if let Some(sender) = header.sender()? {
+            self.service
+                .add_claim(Claim::Proximity, sender.to_owned().into())
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Release proximity sensor polling by client
+    async fn release_proximity(&self, #[zbus(header)] header: MessageHeader<'_>) -> Result<()> {
+        if let Some(sender) = header.sender()? {
+            self.service
+                .remove_claim(Claim::Proximity, sender.to_owned().into())
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+/// iio-sensor-proxy compatible compass interface
+#[derive(Clone)]
+pub struct SensorProxyCompass {
+    service: Service,
+}
+
+impl SensorProxyCompass {
+    pub fn new(service: Service) -> Self {
+        Self { service }
+    }
+}
+
+#[dbus_interface(name = "net.hadess.SensorProxy.Compass")]
+impl SensorProxyCompass {
+    /// Compass available property
+    #[dbus_interface(property)]
+    async fn has_compass(&self) -> bool {
+        self.service.has_sensor(Claim::Compass).await
+    }
+
+    /// Current compass heading in degrees property
+    #[dbus_interface(property)]
+    async fn compass_heading(&self) -> f64 {
+        self.service
+            .state
+            .compass_heading
+            .read()
+            .await
+            .unwrap_or_default()
+    }
+
+    /// Claim compass polling by client
+    async fn claim_compass(&self, #[zbus(header)] header: MessageHeader<'_>) -> Result<()> {
+        if let Some(sender) = header.sender()? {
+            self.service
+                .add_claim(Claim::Compass, sender.to_owned().into())
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Release compass polling by client
+    async fn release_compass(&self, #[zbus(header)] header: MessageHeader<'_>) -> Result<()> {
+        if let Some(sender) = header.sender()? {
+            self.service
+                .remove_claim(Claim::Compass, sender.to_owned().into())
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+impl Service {
+    pub async fn set_sensor_proxy_interfaces(
+        &self,
+        sensor_proxy: InterfaceRef<SensorProxy>,
+        compass: InterfaceRef<SensorProxyCompass>,
+    ) {
+        *self.state.sensor_proxy.write().await = Some(Interfaces {
+            sensor_proxy,
+            compass,
+        });
+    }
+
+    /// Emit iio-sensor-proxy property changes for sensor
+    pub(super) async fn sensor_proxy_changed(&self, claim: Claim, has_changed: bool) -> Result<()> {
+        let ifaces = self.state.sensor_proxy.read().await;
+        let Some(ifaces) = ifaces.as_ref() else {
+            return Ok(());
+        };
+
+        let sensor_proxy = SensorProxy::new(self.clone());
+        let sigctx = ifaces.sensor_proxy.signal_context();
+
+        match claim {
+            Claim::Orientation => {
+                sensor_proxy
+                    .accelerometer_orientation_changed(sigctx)
+                    .await?;
+                if has_changed {
+                    sensor_proxy.has_accelerometer_changed(sigctx).await?;
+                }
+            }
+            Claim::Light => {
+                sensor_proxy.light_level_changed(sigctx).await?;
+                if has_changed {
+                    sensor_proxy.has_ambient_light_changed(sigctx).await?;
+                }
+            }
+            Claim::Proximity => {
+                sensor_proxy.proximity_near_changed(sigctx).await?;
+                if has_changed {
+                    sensor_proxy.has_proximity_changed(sigctx).await?;
+                }
+            }
+            Claim::Compass => {
+                let compass = SensorProxyCompass::new(self.clone());
+                let sigctx = ifaces.compass.signal_context();
+
+                compass.compass_heading_changed(sigctx).await?;
+                if has_changed {
+                    compass.has_compass_changed(sigctx).await?;
+                }
+            }
+            Claim::SensorData => (),
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use zbus::{Message, MessageBuilder};
+
+    /// Method call from client
+    fn call(sender: &str) -> Message {
+        MessageBuilder::method_call(SENSOR_PROXY_PATH, "Claim")
+            .unwrap()
+            .sender(sender)
+            .unwrap()
+            .build(&())
+            .unwrap()
+    }
+
+    #[test]
+    fn orientation_names() {
+        smol::block_on(async {
+            let service = Service::new().unwrap();
+            let proxy = SensorProxy::new(service.clone());
+
+            assert_eq!(proxy.accelerometer_orientation().await, "undefined");
+
+            for (orient, name) in [
+                (Orientation::TopUp, "normal"),
+                (Orientation::BottomUp, "bottom-up"),
+                (Orientation::LeftUp, "left-up"),
+                (Orientation::RightUp, "right-up"),
+            ] {
+                *service.state.orientation.write().await = Some(orient);
+                assert_eq!(proxy.accelerometer_orientation().await, name);
+            }
+        });
+    }
+
+    #[test]
+    fn claims_forwarding() {
+        smol::block_on(async {
+            let service = Service::new().unwrap();
+            let proxy = SensorProxy::new(service.clone());
+            let compass = SensorProxyCompass::new(service.clone());
+            let (first, second) = (call(":1.1"), call(":1.2"));
+            let first = || first.header().unwrap();
+            let second = || second.header().unwrap();
+
+            proxy.claim_accelerometer(first()).await.unwrap();
+            proxy.claim_light(first()).await.unwrap();
+            proxy.claim_proximity(second()).await.unwrap();
+            compass.claim_compass(second()).await.unwrap();
+            assert_eq!(
+                service.claimed_sensors().await,
+                HashSet::from([
+                    Claim::Orientation,
+                    Claim::Light,
+                    Claim::Proximity,
+                    Claim::Compass
+                ])
+            );
+
+            // sensor is kept claimed until all clients releases it
+            proxy.claim_accelerometer(second()).await.unwrap();
+            proxy.release_accelerometer(first()).await.unwrap();
+            assert!(service
+                .claimed_sensors()
+                .await
+                .contains(&Claim::Orientation));
+            proxy.release_accelerometer(second()).await.unwrap();
+            assert!(!service
+                .claimed_sensors()
+                .await
+                .contains(&Claim::Orientation));
+
+            // release by client which does not claim sensor is ignored
+            proxy.release_light(second()).await.unwrap();
+            assert!(service.claimed_sensors().await.contains(&Claim::Light));
+
+            proxy.release_light(first()).await.unwrap();
+            proxy.release_proximity(second()).await.unwrap();
+            compass.release_compass(second()).await.unwrap();
+            assert!(service.claimed_sensors().await.is_empty());
+        });
+    }
+}
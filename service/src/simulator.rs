@@ -0,0 +1,141 @@
+use crate::{
+    AccelLocation, Claim, Config, Detections, Error, Iio, Orientation, Result, Service,
+    TabletModeSource,
+};
+use smol::{
+    channel::{unbounded, Receiver, Sender},
+    future::FutureExt,
+};
+use std::time::Instant;
+use zbus::dbus_interface;
+
+/// Injected accelerometer sample
+type AccelSample = (AccelLocation, [f64; 3]);
+
+/// Simulated sensors interface
+///
+/// Allows to inject data into service state when no hardware is available.
+#[derive(Clone)]
+pub struct Simulator {
+    service: Service,
+    /// Injected samples queue
+    samples: (Sender<AccelSample>, Receiver<AccelSample>),
+}
+
+#[dbus_interface(name = "tablet.assist.Simulator1")]
+impl Simulator {
+    /// Set tablet mode as reported by switch
+    async fn set_tablet_mode(&self, enable: bool) -> Result<()> {
+        self.service
+            .set_tablet_mode(TabletModeSource::Switch, enable)
+            .await
+    }
+
+    /// Set display orientation as detected by sensors
+    async fn set_orientation(&self, orientation: Orientation) -> Result<()> {
+        self.service
+            .set_sensor_available(Claim::Orientation, true)
+            .await?;
+        self.service.set_orientation(orientation).await
+    }
+
+    /// Set lid state as reported by switch
+    async fn set_lid_closed(&self, closed: bool) -> Result<()> {
+        self.service.set_lid_closed(closed).await
+    }
+
+    /// Push accelerometer sample in device frame, m/s^2
+    ///
+    /// The location is `display` or `base`.
+    async fn push_accel(&self, location: &str, x: f64, y: f64, z: f64) -> Result<()> {
+        let location = location.parse().map_err(|_| {
+            Error::DBusFdo(zbus::fdo::Error::InvalidArgs(format!(
+                "Unknown accelerometer location: {location}"
+            )))
+        })?;
+        self.samples
+            .0
+            .send((location, [x, y, z]))
+            .await
+            .map_err(|_| Error::Term)
+    }
+}
+
+impl Simulator {
+    pub fn new(service: Service) -> Self {
+        Self {
+            service,
+            samples: unbounded(),
+        }
+    }
+
+    /// Feed injected samples through sensors pipeline
+    pub async fn process(self, config: &Config) -> Result<Option<async_signal::Signal>> {
+        let mut iio = Iio::new(config);
+        let mut detections = Detections::default();
+        let epoch = Instant::now();
+
+        loop {
+            let sample = async { Some(self.samples.1.recv().await) }
+                .or(async {
+                    // injected samples cannot be used to calibrate real sensors
+                    if let Some(request) = self.service.wait_calibration().await {
+                        let _ = request.reply.try_send(Err(Error::DBusFdo(
+                            zbus::fdo::Error::NotSupported(
+                                "Calibration is not supported in simulation".into(),
+                            ),
+                        )));
+                    }
+                    None
+                })
+                .await;
+            let Some(sample) = sample else {
+                continue;
+            };
+            let Ok((location, value)) = sample else {
+                break;
+            };
+
+            iio.push_accel(location, value, epoch.elapsed());
+
+            for claim in [Claim::Orientation, Claim::SensorData] {
+                if let Err(error) = self
+                    .service
+                    .set_sensor_available(claim, iio.has_sensor(claim))
+                    .await
+                {
+                    tracing::warn!("Error while setting sensor availability: {error}");
+                }
+            }
+
+            let lid_closed = self.service.is_lid_closed().await;
+
+            for detection in detections.update(&iio, lid_closed) {
+                tracing::debug!("Simulated change: {detection:?}");
+                detection.publish(&self.service).await;
+            }
+
+            if self.service.claimed(Claim::SensorData).await {
+                if let Some(data) = iio.display_accel_data() {
+                    if let Err(error) = self.service.set_display_accel(data).await {
+                        tracing::warn!("Error while setting display accel data: {error}");
+                    }
+                }
+                if let Some(data) = iio.base_accel_data() {
+                    if let Err(error) = self.service.set_base_accel(data).await {
+                        tracing::warn!("Error while setting base accel data: {error}");
+                    }
+                }
+            } else {
+                if let Err(error) = self.service.unset_display_accel().await {
+                    tracing::warn!("Error while unsetting display accel data: {error}");
+                }
+                if let Err(error) = self.service.unset_base_accel().await {
+                    tracing::warn!("Error while unsetting base accel data: {error}");
+                }
+            }
+        }
+
+        Err(Error::Term)
+    }
+}
@@ -0,0 +1,273 @@
+#[cfg(feature = "iio")]
+use crate::{AccelLocation, Error};
+use crate::{Orientation, Posture, Result};
+#[cfg(feature = "iio")]
+use std::io::{BufRead, BufReader, Lines};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Input switch which changes recorded to trace
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TraceSwitch {
+    TabletMode,
+    Lid,
+    Dock,
+}
+
+impl FromStr for TraceSwitch {
+    type Err = ();
+    fn from_str(s: &str) -> core::result::Result<Self, Self::Err> {
+        Ok(match s {
+            "tablet-mode" => Self::TabletMode,
+            "lid" => Self::Lid,
+            "dock" => Self::Dock,
+            _ => return Err(()),
+        })
+    }
+}
+
+impl AsRef<str> for TraceSwitch {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::TabletMode => "tablet-mode",
+            Self::Lid => "lid",
+            Self::Dock => "dock",
+        }
+    }
+}
+
+/// Sensor trace event
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TraceEvent {
+    /// Accelerometer parameters provided by device or config
+    ///
+    /// Written before first sample of sensor, so replay converts samples like device does.
+    #[cfg(feature = "iio")]
+    Sensor {
+        location: AccelLocation,
+        /// Mount matrix columns
+        mount: [[f64; 3]; 3],
+        offset: [f64; 3],
+        scale: [f64; 3],
+    },
+    /// Raw accelerometer sample with sample time
+    ///
+    /// The value is read from device before scale, offset, calibration and mount matrix applied.
+    #[cfg(feature = "iio")]
+    Accel {
+        location: AccelLocation,
+        time: Duration,
+        raw: [f64; 3],
+    },
+    /// Input switch state change
+    Switch { switch: TraceSwitch, state: bool },
+    /// Detected display orientation
+    Orientation(Orientation),
+    /// Detected flat state
+    Flat { flat: bool, facing_up: bool },
+    /// Detected tablet mode
+    TabletMode(bool),
+    /// Detected device posture
+    Posture(Posture),
+}
+
+impl core::fmt::Display for TraceEvent {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            #[cfg(feature = "iio")]
+            Self::Sensor {
+                location,
+                mount,
+                offset: [ox, oy, oz],
+                scale: [sx, sy, sz],
+            } => {
+                write!(f, "sensor {}", location.as_ref())?;
+                for value in mount.iter().flatten() {
+                    write!(f, " {value}")?;
+                }
+                write!(f, " {ox} {oy} {oz} {sx} {sy} {sz}")
+            }
+            #[cfg(feature = "iio")]
+            Self::Accel {
+                location,
+                time,
+                raw: [x, y, z],
+            } => write!(
+                f,
+                "accel {} {:.6} {x} {y} {z}",
+                location.as_ref(),
+                time.as_secs_f64()
+            ),
+            Self::Switch { switch, state } => write!(f, "switch {} {state}", switch.as_ref()),
+            Self::Orientation(orientation) => write!(f, "orientation {orientation}"),
+            Self::Flat { flat, facing_up } => write!(f, "flat {flat} {facing_up}"),
+            Self::TabletMode(mode) => write!(f, "tablet-mode {mode}"),
+            Self::Posture(posture) => write!(f, "posture {posture}"),
+        }
+    }
+}
+
+impl FromStr for TraceEvent {
+    type Err = ();
+    fn from_str(s: &str) -> core::result::Result<Self, Self::Err> {
+        let mut args = s.split_whitespace();
+        let mut arg = || args.next().ok_or(());
+
+        Ok(match arg()? {
+            #[cfg(feature = "iio")]
+            "sensor" => {
+                let location = arg()?.parse()?;
+                let mut values = [0f64; 15];
+                for value in &mut values {
+                    *value = arg()?.parse().map_err(|_| ())?;
+                }
+                let [m00, m01, m02, m10, m11, m12, m20, m21, m22, ox, oy, oz, sx, sy, sz] = values;
+                Self::Sensor {
+                    location,
+                    mount: [[m00, m01, m02], [m10, m11, m12], [m20, m21, m22]],
+                    offset: [ox, oy, oz],
+                    scale: [sx, sy, sz],
+                }
+            }
+            #[cfg(feature = "iio")]
+            "accel" => Self::Accel {
+                location: arg()?.parse()?,
+                time: Duration::try_from_secs_f64(arg()?.parse().map_err(|_| ())?)
+                    .map_err(|_| ())?,
+                raw: [
+                    arg()?.parse().map_err(|_| ())?,
+                    arg()?.parse().map_err(|_| ())?,
+                    arg()?.parse().map_err(|_| ())?,
+                ],
+            },
+            "switch" => Self::Switch {
+                switch: arg()?.parse()?,
+                state: arg()?.parse().map_err(|_| ())?,
+            },
+            "orientation" => Self::Orientation(arg()?.parse()?),
+            "flat" => Self::Flat {
+                flat: arg()?.parse().map_err(|_| ())?,
+                facing_up: arg()?.parse().map_err(|_| ())?,
+            },
+            "tablet-mode" => Self::TabletMode(arg()?.parse().map_err(|_| ())?),
+            "posture" => Self::Posture(arg()?.parse()?),
+            _ => return Err(()),
+        })
+    }
+}
+
+/// Write trace line
+///
+/// Each line contains time in seconds since recording start followed by event.
+pub fn write_trace(
+    output: &mut impl Write,
+    time: Duration,
+    event: &TraceEvent,
+) -> std::io::Result<()> {
+    writeln!(output, "{:.6} {event}", time.as_secs_f64())
+}
+
+/// Interval between writes of buffered trace events to file
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Trace file writer shared between sensor tasks
+#[derive(Clone)]
+pub struct Recorder(Arc<Mutex<RecorderState>>);
+
+struct RecorderState {
+    /// Recording start time
+    start: Instant,
+    /// Time of last write to file
+    flushed: Instant,
+    output: BufWriter<File>,
+}
+
+impl Recorder {
+    /// Create new trace file
+    pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+        let file = File::create(path)?;
+        let start = Instant::now();
+        Ok(Self(Arc::new(Mutex::new(RecorderState {
+            start,
+            flushed: start,
+            output: BufWriter::new(file),
+        }))))
+    }
+
+    /// Append event to trace
+    ///
+    /// Events is buffered and written to file when [`FLUSH_INTERVAL`] passed since last write.
+    pub fn record(&self, event: &TraceEvent) -> Result<()> {
+        let mut state = self.0.lock().unwrap();
+        let time = state.start.elapsed();
+        write_trace(&mut state.output, time, event)?;
+        // keep trace mostly complete when service is killed
+        if state.flushed.elapsed() >= FLUSH_INTERVAL {
+            state.output.flush()?;
+            state.flushed = Instant::now();
+        }
+        Ok(())
+    }
+
+    /// Write buffered events to file
+    pub fn flush(&self) -> Result<()> {
+        let mut state = self.0.lock().unwrap();
+        state.output.flush()?;
+        state.flushed = Instant::now();
+        Ok(())
+    }
+}
+
+/// Trace file reader
+#[cfg(feature = "iio")]
+pub struct TraceReader {
+    lines: Lines<BufReader<File>>,
+    line: usize,
+}
+
+#[cfg(feature = "iio")]
+impl TraceReader {
+    /// Open trace file
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self {
+            lines: BufReader::new(File::open(path)?).lines(),
+            line: 0,
+        })
+    }
+}
+
+#[cfg(feature = "iio")]
+impl Iterator for TraceReader {
+    type Item = Result<(Duration, TraceEvent)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(error) => return Some(Err(error.into())),
+            };
+            self.line += 1;
+
+            let line = line.trim();
+            // allow comments in traces attached to reports
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            return Some(
+                line.split_once(' ')
+                    .and_then(|(time, event)| {
+                        let time = Duration::try_from_secs_f64(time.parse().ok()?).ok()?;
+                        Some((time, event.parse().ok()?))
+                    })
+                    .ok_or_else(|| Error::Trace(format!("Bad line {}: {line}", self.line))),
+            );
+        }
+    }
+}
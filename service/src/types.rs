@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use zbus::zvariant::Type;
+use zbus::zvariant::{OwnedValue, Type, Value};
 
 macro_rules! enum_types {
     ($( $(#[$($tmeta:meta)*])* $type:ident { $( $(#[$($vmeta:meta)*])* $var:ident = $val:literal, )* } )*) => {
@@ -109,6 +109,31 @@ enum_types! {
         RightUp = "right-up",
         BottomUp = "bottom-up",
     }
+
+    CalibrationPose {
+        /// Device stands on bottom edge with display facing user
+        #[default]
+        TopUp = "top-up",
+        /// Device stands on top edge
+        BottomUp = "bottom-up",
+        /// Device stands on right edge
+        LeftUp = "left-up",
+        /// Device stands on left edge
+        RightUp = "right-up",
+        /// Device lies flat with display facing up
+        FaceUp = "face-up",
+        /// Device lies flat with display facing down
+        FaceDown = "face-down",
+    }
+
+    Posture {
+        #[default]
+        Laptop = "laptop",
+        Tent = "tent",
+        Stand = "stand",
+        Tablet = "tablet",
+        Closed = "closed",
+    }
 }
 
 impl Orientation {
@@ -126,3 +151,22 @@ impl From<Orientation> for OrientationType {
         }
     }
 }
+
+/// Accelerometer measurements
+#[derive(Debug, Clone, Copy, Default, PartialEq, Type, Value, OwnedValue)]
+pub struct AccelData {
+    /// Calibrated gravity vector X component
+    pub x: f64,
+    /// Calibrated gravity vector Y component
+    pub y: f64,
+    /// Calibrated gravity vector Z component
+    pub z: f64,
+    /// Angle in XY plane in degrees
+    pub xy_angle: f64,
+    /// Angle to XY plane in degrees
+    pub z_angle: f64,
+    /// Angular velocity in degrees per second
+    pub velocity: f64,
+    /// Angular acceleration in degrees per second^2
+    pub acceleration: f64,
+}